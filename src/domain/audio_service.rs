@@ -1,5 +1,19 @@
+use crate::domain::calibration::CabinProfile;
+use crate::domain::driver_seat::DriverSeat;
+use crate::domain::eq::CustomEq;
+use crate::domain::head_unit::HeadUnitMode;
+use crate::domain::lyrics::Lyrics;
+use crate::domain::mono_check::MonoCheck;
+use crate::domain::music_analysis::MusicalKey;
+use crate::domain::output_format::OutputFormat;
+use crate::domain::provenance::Provenance;
+use crate::domain::tag_edits::TagEdits;
+use crate::domain::track_edges::{Fades, SilenceTrim};
+use crate::domain::track_tags::TrackPosition;
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,18 +25,560 @@ pub enum AudioError {
     ProcessingError(String),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum AudioPreset {
     CarBass,
     PureHiFi,
     ExtremeLow,
-    Surround8D,
+    Surround8D(SurroundParams),
+    // Личный эквалайзер пользователя (/eq)
+    Custom(CustomEq),
+    // Штатные динамики: глубокий саб заменяем его гармониками
+    StockSpeakers(VirtualBassParams),
+    // Ускорение/замедление (slowed, sped up, nightcore)
+    Tempo(TempoParams),
+    // Свёрточный ревербератор на импульсном отклике
+    Reverb(ReverbParams),
+    Limiter,
+    // Чистка живых записей и рипов: шум, фон сети, щелчки
+    Cleanup(CleanupStrength),
+    // Караоке или, наоборот, голый вокал (по центру стереопанорамы)
+    Vocals(VocalMode),
+    // Режим "Трасса": многополосное сжатие, чтобы тихие места не тонули в шуме дороги
+    Highway(HighwayStrength),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoParams {
+    // Во сколько раз быстрее (меньше 1.0 — медленнее)
+    pub factor: f32,
+    // true — меняем только темп (atempo), false — темп вместе с высотой, как на пластинке
+    pub keep_pitch: bool,
+}
+
+impl TempoParams {
+    pub const SLOWED: Self = Self {
+        factor: 0.85,
+        keep_pitch: false,
+    };
+    pub const SPED_UP: Self = Self {
+        factor: 1.25,
+        keep_pitch: true,
+    };
+    pub const NIGHTCORE: Self = Self {
+        factor: 1.25,
+        keep_pitch: false,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupStrength {
+    Light,
+    Medium,
+    Strong,
+}
+
+impl CleanupStrength {
+    // Насколько давим шумовой фон, дБ
+    pub fn noise_reduction_db(&self) -> f32 {
+        match self {
+            CleanupStrength::Light => 6.0,
+            CleanupStrength::Medium => 12.0,
+            CleanupStrength::Strong => 20.0,
+        }
+    }
+
+    // Сколько гармоник фона сети (50 и 60 Гц) вырезаем
+    pub fn hum_harmonics(&self) -> u32 {
+        match self {
+            CleanupStrength::Light => 2,
+            CleanupStrength::Medium => 3,
+            CleanupStrength::Strong => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VocalMode {
+    // Убираем центр (вокал), но бас и бочку оставляем
+    Instrumental,
+    // Оставляем только центр без баса
+    VocalsOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighwayStrength {
+    Light,
+    Medium,
+    Strong,
+}
+
+impl HighwayStrength {
+    // На сколько дБ поднимаем тихие места (около -50 dBFS)
+    pub fn lift_db(&self) -> f32 {
+        match self {
+            HighwayStrength::Light => 6.0,
+            HighwayStrength::Medium => 10.0,
+            HighwayStrength::Strong => 14.0,
+        }
+    }
+
+    // Кривая компрессора (вход/выход, дБ): тихое поднимаем, выше -20 dBFS не трогаем,
+    // а совсем тишину (шум записи) не вытягиваем
+    pub fn transfer_points(&self) -> [(f32, f32); 6] {
+        let lift = self.lift_db();
+        [
+            (-90.0, -90.0),
+            (-70.0, -70.0 + lift * 0.5),
+            (-50.0, -50.0 + lift),
+            (-30.0, -30.0 + lift * 0.6),
+            (-20.0, -20.0),
+            (0.0, 0.0),
+        ]
+    }
+}
+
+// Импульсные отклики, которые идут вместе с ботом
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Room {
+    Plate,
+    Hall,
+    Club,
+    Cathedral,
+    Small,
+}
+
+impl Room {
+    pub const ALL: [Room; 5] = [
+        Room::Plate,
+        Room::Hall,
+        Room::Club,
+        Room::Cathedral,
+        Room::Small,
+    ];
+
+    // Имя файла отклика (без расширения)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Room::Plate => "plate",
+            Room::Hall => "hall",
+            Room::Club => "club",
+            Room::Cathedral => "cathedral",
+            Room::Small => "small_room",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbParams {
+    pub room: Room,
+    // Доля "мокрого" сигнала, 0.0..=1.0
+    pub mix: f32,
+}
+
+// Этапы цепочки в порядке обработки. Из каждого этапа в задании
+// может быть не больше одного пресета.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PresetStage {
+    Cleanup,
+    Vocal,
+    Tempo,
+    Tone,
+    Spatial,
+    Reverb,
+    Dynamics,
+}
+
+// Целевая громкость, к которой нормализуем трек перед эффектами
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessTarget {
+    pub lufs: f32,
+    pub true_peak: f32,
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self {
+            lufs: -14.0,
+            true_peak: -1.5,
+        }
+    }
+}
+
+impl AudioPreset {
+    pub fn stage(&self) -> PresetStage {
+        match self {
+            AudioPreset::Cleanup(_) => PresetStage::Cleanup,
+            AudioPreset::Vocals(_) => PresetStage::Vocal,
+            AudioPreset::Tempo(_) => PresetStage::Tempo,
+            AudioPreset::CarBass
+            | AudioPreset::PureHiFi
+            | AudioPreset::ExtremeLow
+            | AudioPreset::Custom(_)
+            | AudioPreset::StockSpeakers(_) => PresetStage::Tone,
+            AudioPreset::Surround8D(_) => PresetStage::Spatial,
+            AudioPreset::Reverb(_) => PresetStage::Reverb,
+            AudioPreset::Limiter | AudioPreset::Highway(_) => PresetStage::Dynamics,
+        }
+    }
+
+    // Во сколько раз меняется длительность трека (делитель)
+    pub fn tempo_factor(&self) -> f32 {
+        match self {
+            AudioPreset::Tempo(params) => params.factor,
+            _ => 1.0,
+        }
+    }
+
+    // Во сколько раз поднимается высота (slowed и nightcore "как на пластинке")
+    pub fn pitch_factor(&self) -> f32 {
+        match self {
+            AudioPreset::Tempo(params) if !params.keep_pitch => params.factor,
+            _ => 1.0,
+        }
+    }
+
+    // Своя целевая громкость есть только у тональных пресетов
+    pub fn loudness(&self) -> Option<LoudnessTarget> {
+        let (lufs, true_peak) = match self {
+            AudioPreset::CarBass | AudioPreset::StockSpeakers(_) => (-14.0, -1.5),
+            AudioPreset::PureHiFi => (-16.0, -1.5),
+            AudioPreset::ExtremeLow => (-12.0, -1.0),
+            AudioPreset::Custom(eq) => (eq.loudness, -1.5),
+            _ => return None,
+        };
+        Some(LoudnessTarget { lufs, true_peak })
+    }
+}
+
+// Параметры бинаурального 8D: источник вращается вокруг головы слушателя
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurroundParams {
+    // Скорость вращения (оборотов в секунду)
+    pub rotation_hz: f32,
+    // Амплитуда качания источника по высоте (градусы)
+    pub elevation_deg: f32,
+    // Доля "комнаты" в итоговом звуке, 0.0..=1.0
+    pub reverb: f32,
+}
+
+impl Default for SurroundParams {
+    fn default() -> Self {
+        Self {
+            rotation_hz: 0.1,
+            elevation_deg: 20.0,
+            reverb: 0.25,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SurroundError {
+    #[error("Скорость вращения {0} об/с вне диапазона 0.02–1")]
+    RotationOutOfRange(f32),
+
+    #[error("Качание по высоте {0}° вне диапазона 0–60")]
+    ElevationOutOfRange(f32),
+
+    #[error("Доля комнаты {0}% вне диапазона 0–100")]
+    ReverbOutOfRange(f32),
+
+    #[error("Формат: скорость [высота] [комната%], например 0.1 20 25%")]
+    InvalidFormat,
+}
+
+impl SurroundParams {
+    pub fn new(rotation_hz: f32, elevation_deg: f32, reverb: f32) -> Result<Self, SurroundError> {
+        if !(0.02..=1.0).contains(&rotation_hz) {
+            return Err(SurroundError::RotationOutOfRange(rotation_hz));
+        }
+        if !(0.0..=60.0).contains(&elevation_deg) {
+            return Err(SurroundError::ElevationOutOfRange(elevation_deg));
+        }
+        if !(0.0..=1.0).contains(&reverb) {
+            return Err(SurroundError::ReverbOutOfRange(reverb * 100.0));
+        }
+        Ok(Self {
+            rotation_hz,
+            elevation_deg,
+            reverb,
+        })
+    }
+}
+
+// "0.1 20 25%" — скорость, высота и комната, как в команде /8d
+impl fmt::Display for SurroundParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}%",
+            self.rotation_hz,
+            self.elevation_deg,
+            (self.reverb * 100.0).round()
+        )
+    }
+}
+
+// Недостающие числа берутся по умолчанию: "/8d 0.2" меняет только скорость
+impl FromStr for SurroundParams {
+    type Err = SurroundError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .trim_end_matches(['%', '°'])
+                    .parse::<f32>()
+                    .map_err(|_| SurroundError::InvalidFormat)
+            })
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        let default = SurroundParams::default();
+        match numbers.as_slice() {
+            [rotation] => SurroundParams::new(*rotation, default.elevation_deg, default.reverb),
+            [rotation, elevation] => SurroundParams::new(*rotation, *elevation, default.reverb),
+            [rotation, elevation, reverb] => {
+                SurroundParams::new(*rotation, *elevation, reverb / 100.0)
+            }
+            _ => Err(SurroundError::InvalidFormat),
+        }
+    }
+}
+
+// Психоакустический бас: ниже cutoff_hz динамик не нагружаем,
+// а вместо этого добавляем гармоники, по которым мозг "достраивает" бас
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualBassParams {
+    pub cutoff_hz: f32,
+}
+
+impl VirtualBassParams {
+    // Чем меньше диаметр диффузора, тем выше частота, которую он ещё тянет
+    pub fn for_speaker_size(diameter_cm: f32) -> Self {
+        Self {
+            cutoff_hz: (1300.0 / diameter_cm.max(1.0)).clamp(45.0, 150.0).round(),
+        }
+    }
+}
+
+impl Default for VirtualBassParams {
+    // Самый частый штатный размер — 16.5 см (6.5")
+    fn default() -> Self {
+        Self::for_speaker_size(16.5)
+    }
+}
+
+// Что получаем на выходе
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputMode {
+    // Обычный стерео-файл
+    #[default]
+    Stereo,
+    // Раздельные файлы для отдельного усилителя: моно-саб и стерео-фронт
+    Crossover(CrossoverParams),
+    // Апмикс в 5.1 для магнитол с многоканальным воспроизведением с флешки
+    Surround(UpmixParams),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CrossoverError {
+    #[error("Частота раздела {0} Гц вне диапазона 40–250")]
+    FrequencyOutOfRange(f32),
+
+    #[error("Крутизна среза {0} не поддерживается: 12, 24 или 48 дБ/окт")]
+    InvalidSlope(u32),
+
+    #[error("Формат: частота [крутизна], например 80 24")]
+    InvalidFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossoverParams {
+    pub freq_hz: f32,
+    // Крутизна среза, дБ/октаву: 12, 24 или 48
+    pub slope_db: u32,
+}
+
+impl CrossoverParams {
+    pub fn new(freq_hz: f32, slope_db: u32) -> Result<Self, CrossoverError> {
+        if !(40.0..=250.0).contains(&freq_hz) {
+            return Err(CrossoverError::FrequencyOutOfRange(freq_hz));
+        }
+        if ![12, 24, 48].contains(&slope_db) {
+            return Err(CrossoverError::InvalidSlope(slope_db));
+        }
+        Ok(Self { freq_hz, slope_db })
+    }
+}
+
+// "80 24" — частота и крутизна, как в команде /crossover
+impl fmt::Display for CrossoverParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.freq_hz, self.slope_db)
+    }
+}
+
+impl FromStr for CrossoverParams {
+    type Err = CrossoverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let freq = parts.next().and_then(|v| v.parse().ok());
+        let slope = match parts.next() {
+            Some(v) => v.parse().ok(),
+            None => Some(24),
+        };
+
+        match (freq, slope) {
+            (Some(freq), Some(slope)) => CrossoverParams::new(freq, slope),
+            _ => Err(CrossoverError::InvalidFormat),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum UpmixError {
+    #[error("Формат «{0}» не поддерживается: flac или ac3")]
+    UnknownCodec(String),
+
+    #[error("Не понял «{0}» — нужно число в дБ")]
+    InvalidNumber(String),
+
+    #[error("Уровень {0} дБ вне диапазона -12…+6")]
+    LevelOutOfRange(f32),
+}
+
+// Контейнер для многоканального файла
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultichannelCodec {
+    Flac,
+    Ac3,
+}
+
+impl MultichannelCodec {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MultichannelCodec::Flac => "flac",
+            MultichannelCodec::Ac3 => "ac3",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpmixParams {
+    pub codec: MultichannelCodec,
+    // Уровни центра и сабвуферного канала относительно фронта, дБ
+    pub center_db: f32,
+    pub lfe_db: f32,
+}
+
+impl UpmixParams {
+    pub fn new(codec: MultichannelCodec, center_db: f32, lfe_db: f32) -> Result<Self, UpmixError> {
+        for level in [center_db, lfe_db] {
+            if !(-12.0..=6.0).contains(&level) {
+                return Err(UpmixError::LevelOutOfRange(level));
+            }
+        }
+        Ok(Self {
+            codec,
+            center_db,
+            lfe_db,
+        })
+    }
+}
+
+// "flac 0 -3" — формат, центр и LFE, как в команде /surround
+impl fmt::Display for UpmixParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.codec.extension(),
+            self.center_db,
+            self.lfe_db
+        )
+    }
+}
+
+impl FromStr for UpmixParams {
+    type Err = UpmixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let codec = match parts.next().map(str::to_lowercase).as_deref() {
+            Some("flac") | None => MultichannelCodec::Flac,
+            Some("ac3") => MultichannelCodec::Ac3,
+            Some(other) => return Err(UpmixError::UnknownCodec(other.to_string())),
+        };
+        let mut level = || -> Result<f32, UpmixError> {
+            match parts.next() {
+                Some(v) => v
+                    .parse()
+                    .map_err(|_| UpmixError::InvalidNumber(v.to_string())),
+                None => Ok(0.0),
+            }
+        };
+        let center_db = level()?;
+        let lfe_db = level()?;
+        UpmixParams::new(codec, center_db, lfe_db)
+    }
+}
+
+// Всё, что нужно знать процессору о задании
+#[derive(Debug, Clone)]
+pub struct ProcessingOptions {
+    // Эффекты задания; процессор сам выстраивает их по PresetStage
+    pub presets: Vec<AudioPreset>,
+    // Коррекция салона пользователя, накладывается поверх любого пресета
+    pub cabin: Option<CabinProfile>,
+    // Выравнивание под место водителя
+    pub seat: Option<DriverSeat>,
+    pub output: OutputMode,
+    // Формат стерео-файлов (кроме 5.1)
+    pub format: OutputFormat,
+    // Обрезка тишины и фейды по краям трека
+    pub trim: Option<SilenceTrim>,
+    pub fades: Option<Fades>,
+    // Совместимость со штатной магнитолой: теги, обложка, MP3 44.1 кГц
+    pub head_unit: Option<HeadUnitMode>,
+    // Правки тегов пользователя и его обложка (уже скачанная)
+    pub tags: TagEdits,
+    pub custom_cover: Option<PathBuf>,
+    // Версия для моно-колонки: 8D без HRTF, чтобы каналы не гасили друг друга
+    pub mono_safe: bool,
+    // Чем обработали — пишется в теги, чтобы не обработать файл дважды
+    pub provenance: Provenance,
+}
+
+// Готовый файл; suffix отличает части одного трека ("Sub", "Mains")
+#[derive(Debug, Clone)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    pub suffix: Option<String>,
+    // Расширение файла для отправки ("mp3", "flac", ...)
+    pub extension: &'static str,
+    // Проверка сложения в моно (только для стерео-файлов)
+    pub mono: Option<MonoCheck>,
 }
 
 pub struct AudioMetadata {
     pub title: String,
     pub artist: String,
-    pub thumbnail_url: Option<String>,
+    pub album: Option<String>,
+    pub year: Option<i32>,
+    pub genre: Option<String>,
+    pub track: Option<TrackPosition>,
+    // Страница ролика — в ID3 уходит как WOAS
+    pub source_url: Option<String>,
+    pub comment: Option<String>,
+    // Текст из ручных субтитров ролика, время — уже по готовому треку
+    pub lyrics: Option<Lyrics>,
+    // Темп и тональность готового трека (с учётом slowed/nightcore)
+    pub bpm: Option<f32>,
+    pub key: Option<MusicalKey>,
+    // Превью для обложки, от лучшего к худшему
+    pub thumbnails: Vec<String>,
     pub duration: u64,
 }
 
@@ -31,6 +587,20 @@ pub trait AudioService: Send + Sync {
     async fn process_track(
         &self,
         url: &str,
-        preset: AudioPreset,
-    ) -> Result<(PathBuf, AudioMetadata), AudioError>;
+        options: &ProcessingOptions,
+    ) -> Result<(Vec<OutputFile>, AudioMetadata), AudioError>;
+
+    // То же для аудиофайла, присланного пользователем
+    async fn process_upload(
+        &self,
+        path: &Path,
+        file_name: &str,
+        options: &ProcessingOptions,
+    ) -> Result<(Vec<OutputFile>, AudioMetadata), AudioError>;
+
+    // Теги происхождения, если файл уже прошёл через бота
+    async fn read_provenance(&self, path: &Path) -> Option<Provenance>;
+
+    // Даст ли mono_safe другой звук для этих эффектов — иначе повтор бессмыслен
+    fn has_mono_safe_variant(&self, presets: &[AudioPreset]) -> bool;
 }
@@ -0,0 +1,112 @@
+use crate::domain::title_rules::TitleRules;
+use crate::domain::track_name::{TrackSource, parse_track_name};
+use crate::domain::track_tags::{TrackPosition, release_year};
+use std::path::Path;
+
+// Больше Bot API скачивать не даёт
+pub const MAX_UPLOAD_BYTES: u32 = 20 * 1024 * 1024;
+
+// Что переносим из тегов присланного файла в готовый трек
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadedTags {
+    pub title: String,
+    pub artist: String,
+    pub album: Option<String>,
+    pub year: Option<i32>,
+    pub genre: Option<String>,
+    pub track: Option<TrackPosition>,
+    pub comment: Option<String>,
+}
+
+// Расширение для временного файла: только буквы и цифры из имени пользователя
+pub fn upload_extension(file_name: &str) -> String {
+    Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("audio")
+        .to_lowercase()
+}
+
+// Теги файла, а без них — разбор имени "Исполнитель - Название.mp3".
+// tag ищет значение по имени без учёта регистра (у форматов он разный)
+pub fn uploaded_tags<'a>(
+    tag: impl Fn(&str) -> Option<&'a str>,
+    file_name: &str,
+    rules: &TitleRules,
+) -> UploadedTags {
+    let stem = Path::new(file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name);
+    let title = rules.clean(tag("title").unwrap_or(stem));
+    let name = parse_track_name(TrackSource {
+        title: &title,
+        artist: tag("artist"),
+        track: tag("title").map(|_| title.as_str()),
+        ..Default::default()
+    });
+
+    UploadedTags {
+        title: name.tag_title(),
+        artist: name.artist,
+        album: tag("album").map(str::to_string),
+        year: tag("date").and_then(|d| release_year(None, Some(d))),
+        genre: tag("genre").map(str::to_string),
+        track: tag("track").and_then(|t| t.parse().ok()),
+        comment: tag("comment").map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup<'a>(tags: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<&'a str> {
+        move |key| {
+            tags.iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| *v)
+        }
+    }
+
+    #[test]
+    fn maps_file_tags_and_falls_back_to_file_name() {
+        let rules = TitleRules::default();
+        let tags = [
+            ("TITLE", "Кукушка"),
+            ("ARTIST", "Кино"),
+            ("album", "Чёрный альбом"),
+            ("date", "1990-01-12"),
+            ("track", "3/10"),
+            ("genre", "Rock"),
+        ];
+        let mapped = uploaded_tags(lookup(&tags), "track01.flac", &rules);
+        assert_eq!(mapped.title, "Кукушка");
+        assert_eq!(mapped.artist, "Кино");
+        assert_eq!(mapped.album.as_deref(), Some("Чёрный альбом"));
+        assert_eq!(mapped.year, Some(1990));
+        assert_eq!(mapped.track.map(|t| t.to_string()), Some("3/10".into()));
+
+        // Без тегов и с пустым TPE1 — из имени файла, без мусора
+        let mapped = uploaded_tags(
+            lookup(&[("artist", ",")]),
+            "Кино - Группа крови (Official Video).mp3",
+            &rules,
+        );
+        assert_eq!(
+            (mapped.artist.as_str(), mapped.title.as_str()),
+            ("Кино", "Группа крови")
+        );
+        assert_eq!(mapped.track, None);
+    }
+
+    #[test]
+    fn keeps_only_safe_extensions() {
+        assert_eq!(upload_extension("Трек.MP3"), "mp3");
+        assert_eq!(upload_extension("voice.m4a"), "m4a");
+        assert_eq!(upload_extension("../../etc/passwd"), "audio");
+        assert_eq!(upload_extension("song.mp3;rm -rf"), "audio");
+        assert_eq!(upload_extension("без расширения"), "audio");
+    }
+}
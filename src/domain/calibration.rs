@@ -0,0 +1,177 @@
+use crate::domain::audio_service::AudioError;
+use crate::domain::dsp::{for_each_power_spectrum, to_db};
+use crate::domain::eq::{EqBand, EqError};
+use async_trait::async_trait;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Центры третьоктавных полос, в которых меряем салон.
+// Ниже 63 Гц и выше 10 кГц микрофон телефона врёт сильнее, чем машина.
+const BAND_CENTERS: [f32; 23] = [
+    63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0, 500.0, 630.0, 800.0, 1000.0,
+    1250.0, 1600.0, 2000.0, 2500.0, 3150.0, 4000.0, 5000.0, 6300.0, 8000.0, 10000.0,
+];
+
+// Q третьоктавного фильтра
+const BAND_Q: f32 = 4.3;
+
+// Сколько можно вырезать и сколько поднимать: провалы в салоне — это
+// интерференция, их бустом не вытащить, только перегрузим усилитель
+const MAX_CUT_DB: f32 = 9.0;
+const MAX_BOOST_DB: f32 = 4.0;
+
+// Мельче этого не корректируем
+const MIN_CORRECTION_DB: f32 = 1.0;
+
+const FRAME: usize = 8192;
+
+// Корректирующая АЧХ салона конкретного пользователя
+#[derive(Debug, Clone, PartialEq)]
+pub struct CabinProfile {
+    pub bands: Vec<EqBand>,
+}
+
+impl fmt::Display for CabinProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bands: Vec<String> = self.bands.iter().map(|b| b.to_string()).collect();
+        write!(f, "{}", bands.join(" "))
+    }
+}
+
+impl FromStr for CabinProfile {
+    type Err = EqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bands = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<EqBand>, _>>()?;
+        Ok(Self { bands })
+    }
+}
+
+#[async_trait]
+pub trait CalibrationService: Send + Sync {
+    // Файл с тестовым сигналом (розовый шум), который пользователь включает в машине
+    async fn make_test_signal(&self) -> Result<PathBuf, AudioError>;
+
+    // Разбор записи тестового сигнала, сделанной телефоном в салоне
+    async fn analyse_recording(&self, path: &Path) -> Result<CabinProfile, AudioError>;
+}
+
+// Уровни третьоктавных полос записи (дБ, абсолютные).
+// Берём только громкие кадры — тишину до и после включения сигнала отбрасываем.
+pub fn measure_band_levels(samples: &[f32], sample_rate: u32) -> Option<Vec<f32>> {
+    if samples.len() < FRAME {
+        return None;
+    }
+
+    // Сначала только энергия кадров — спектры храним не все, а сразу суммируем
+    let loudest = (0..=samples.len() - FRAME)
+        .step_by(FRAME / 2)
+        .map(|start| {
+            let chunk = &samples[start..start + FRAME];
+            chunk.iter().map(|s| s * s).sum::<f32>() / FRAME as f32
+        })
+        .fold(0.0, f32::max);
+    if loudest <= 0.0 {
+        return None;
+    }
+
+    // Кадры не тише -10 дБ от самого громкого
+    let mut average = vec![0.0f32; FRAME / 2 + 1];
+    let mut used = 0;
+    for_each_power_spectrum(samples, FRAME, FRAME / 2, |energy, power| {
+        if energy >= loudest * 0.1 {
+            for (acc, p) in average.iter_mut().zip(power) {
+                *acc += p;
+            }
+            used += 1;
+        }
+    });
+    if used == 0 {
+        return None;
+    }
+
+    let bin_hz = sample_rate as f32 / FRAME as f32;
+    let edge = 2f32.powf(1.0 / 6.0);
+    let levels = BAND_CENTERS
+        .iter()
+        .map(|center| {
+            let lo = ((center / edge) / bin_hz).ceil() as usize;
+            let hi = (((center * edge) / bin_hz).floor() as usize).min(average.len() - 1);
+            let energy: f32 = average[lo..=hi.max(lo)].iter().sum();
+            to_db(energy / used as f32)
+        })
+        .collect();
+    Some(levels)
+}
+
+// Из уровней полос под розовым шумом (в идеале — ровная линия)
+// строим сглаженную корректирующую кривую
+pub fn corrective_profile(levels: &[f32]) -> CabinProfile {
+    let mut sorted = levels.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let median = sorted.get(sorted.len() / 2).copied().unwrap_or(0.0);
+
+    let deviation: Vec<f32> = levels.iter().map(|l| l - median).collect();
+
+    // Сглаживание по трём соседним полосам: узкие пики — это уже шум замера
+    let smoothed: Vec<f32> = (0..deviation.len())
+        .map(|i| {
+            let from = i.saturating_sub(1);
+            let to = (i + 2).min(deviation.len());
+            deviation[from..to].iter().sum::<f32>() / (to - from) as f32
+        })
+        .collect();
+
+    let bands = BAND_CENTERS
+        .iter()
+        .zip(&smoothed)
+        .filter_map(|(&freq, &dev)| {
+            let gain = (-dev).clamp(-MAX_CUT_DB, MAX_BOOST_DB);
+            if gain.abs() < MIN_CORRECTION_DB {
+                return None;
+            }
+            EqBand::new(freq, (gain * 10.0).round() / 10.0, BAND_Q).ok()
+        })
+        .collect();
+
+    CabinProfile { bands }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_response_needs_no_correction() {
+        let profile = corrective_profile(&[-20.0; BAND_CENTERS.len()]);
+        assert!(profile.bands.is_empty());
+    }
+
+    #[test]
+    fn resonance_is_cut_and_dip_is_boosted_within_limits() {
+        let mut levels = [-20.0; BAND_CENTERS.len()];
+        levels[3] = 10.0; // 125 Гц гудит на +30 дБ
+        levels[15] = -40.0; // 2 кГц провал на -20 дБ
+
+        let profile = corrective_profile(&levels);
+        let at = |freq: f32| profile.bands.iter().find(|b| b.freq == freq).unwrap();
+
+        assert_eq!(at(125.0).gain, -MAX_CUT_DB);
+        assert_eq!(at(2000.0).gain, MAX_BOOST_DB);
+    }
+
+    #[test]
+    fn profile_round_trips_through_text() {
+        let profile = CabinProfile {
+            bands: vec![EqBand::new(125.0, -6.5, BAND_Q).unwrap()],
+        };
+        assert_eq!(
+            profile.to_string().parse::<CabinProfile>().unwrap(),
+            profile
+        );
+    }
+}
@@ -0,0 +1,199 @@
+// Меньше этого обложка на экране магнитолы превращается в кашу
+pub const MIN_COVER_SIDE: u32 = 200;
+
+// Какую долю площади кадра должна занимать найденная картинка, чтобы ей поверить
+const MIN_CROP_SHARE: f32 = 0.25;
+
+// Настоящий формат картинки по первым байтам: YouTube отдаёт и JPEG, и WebP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Gif,
+}
+
+impl ImageFormat {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"\x89PNG") {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+            Some(ImageFormat::Webp)
+        } else if bytes.starts_with(b"GIF8") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Gif => "image/gif",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+            ImageFormat::Gif => "gif",
+        }
+    }
+}
+
+// Готовая обложка для тегов
+#[derive(Debug, Clone)]
+pub struct CoverImage {
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+}
+
+// Вариант превью из метаданных ролика
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailCandidate {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    // Насколько сам источник считает превью хорошим (у yt-dlp — preference)
+    pub preference: Option<i32>,
+}
+
+// Порядок, в котором пробуем превью: сначала лучшие по мнению источника,
+// при равенстве — крупные. Заведомо мелкие отбрасываем.
+pub fn rank_thumbnails(candidates: &[ThumbnailCandidate]) -> Vec<String> {
+    let mut ranked: Vec<&ThumbnailCandidate> = candidates
+        .iter()
+        .filter(|c| {
+            c.width
+                .zip(c.height)
+                .is_none_or(|(w, h)| w.min(h) >= MIN_COVER_SIDE)
+        })
+        .collect();
+    ranked.sort_by_key(|c| {
+        let area = c.width.unwrap_or(0) as u64 * c.height.unwrap_or(0) as u64;
+        std::cmp::Reverse((c.preference.unwrap_or(i32::MIN), area))
+    });
+
+    let mut urls: Vec<String> = Vec::new();
+    for candidate in ranked {
+        if !urls.contains(&candidate.url) {
+            urls.push(candidate.url.clone());
+        }
+    }
+    urls
+}
+
+// Область картинки без чёрных полей (вывод cropdetect)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropBox {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+// Последнее "crop=w:h:x:y" из лога cropdetect — к нему детектор уже сошёлся
+pub fn parse_cropdetect(log: &str) -> Option<CropBox> {
+    let (_, last) = log.rsplit_once("crop=")?;
+    let numbers: Vec<u32> = last
+        .split_whitespace()
+        .next()?
+        .split(':')
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match numbers.as_slice() {
+        [width, height, x, y] if *width > 0 && *height > 0 => Some(CropBox {
+            width: *width,
+            height: *height,
+            x: *x,
+            y: *y,
+        }),
+        _ => None,
+    }
+}
+
+impl CropBox {
+    // На тёмных обложках cropdetect находит крошечную рамку вокруг светлого пятна —
+    // такую обрезку не берём, оставляем кадр целиком
+    pub fn is_plausible(&self, source_width: u32, source_height: u32) -> bool {
+        let fits = self.x + self.width <= source_width && self.y + self.height <= source_height;
+        let share =
+            (self.width as f32 * self.height as f32) / (source_width as f32 * source_height as f32);
+        fits && self.width.min(self.height) >= MIN_COVER_SIDE && share >= MIN_CROP_SHARE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_real_image_format() {
+        assert_eq!(
+            ImageFormat::detect(b"RIFF\x10\0\0\0WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(
+            ImageFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(ImageFormat::detect(b"<!DOCTYPE html>"), None);
+    }
+
+    #[test]
+    fn ranks_thumbnails_by_preference_then_size() {
+        let thumb = |url: &str, size: Option<u32>, preference: i32| ThumbnailCandidate {
+            url: url.to_string(),
+            width: size.map(|s| s * 16 / 9),
+            height: size,
+            preference: Some(preference),
+        };
+        let ranked = rank_thumbnails(&[
+            thumb("tiny", Some(90), 0),
+            thumb("hq", Some(360), -5),
+            thumb("maxres", Some(720), -5),
+            thumb("webp", None, -1),
+        ]);
+        assert_eq!(ranked, ["webp", "maxres", "hq"]);
+    }
+
+    #[test]
+    fn parses_last_cropdetect_result() {
+        let log = "\
+[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1279 y1:90 y2:629 w:1280 h:540 x:0 y:90 pts:0 t:0.000000 limit:24 crop=1280:540:0:90
+[Parsed_cropdetect_0 @ 0x1] x1:0 x2:1279 y1:92 y2:627 w:1280 h:536 x:0 y:92 pts:1 t:0.040000 limit:24 crop=1280:536:0:92";
+        assert_eq!(
+            parse_cropdetect(log),
+            Some(CropBox {
+                width: 1280,
+                height: 536,
+                x: 0,
+                y: 92
+            })
+        );
+        assert_eq!(parse_cropdetect("no frames"), None);
+    }
+
+    #[test]
+    fn rejects_tiny_crop_boxes() {
+        let crop = |width, height, x, y| CropBox {
+            width,
+            height,
+            x,
+            y,
+        };
+        // Квадратная обложка в 16:9 с полями по бокам
+        assert!(crop(720, 720, 280, 0).is_plausible(1280, 720));
+        // Светлое пятно на тёмной обложке
+        assert!(!crop(64, 48, 600, 300).is_plausible(1280, 720));
+        assert!(!crop(300, 300, 0, 0).is_plausible(1280, 720));
+        assert!(!crop(720, 720, 800, 0).is_plausible(1280, 720));
+    }
+}
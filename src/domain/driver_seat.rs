@@ -0,0 +1,131 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Скорость звука, см за миллисекунду
+const SOUND_CM_PER_MS: f32 = 34.3;
+
+// Больше не компенсируем громкость: дальше уже слышно, как "проваливается" сцена
+const MAX_BALANCE_DB: f32 = 6.0;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SeatError {
+    #[error("Нужно два расстояния в сантиметрах: до левого и до правого динамика")]
+    MissingDistance,
+
+    #[error("Не понял «{0}» — нужно число")]
+    InvalidNumber(String),
+
+    #[error("Расстояние {0} см вне диапазона 20–400")]
+    DistanceOutOfRange(f32),
+
+    #[error("Ширина стерео {0}% вне диапазона 0–200")]
+    WidthOutOfRange(f32),
+}
+
+// Геометрия места водителя: расстояния от головы до передних динамиков
+// и (по желанию) ширина стереобазы в процентах
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriverSeat {
+    pub left_cm: f32,
+    pub right_cm: f32,
+    pub width_percent: f32,
+}
+
+impl DriverSeat {
+    pub fn new(left_cm: f32, right_cm: f32, width_percent: f32) -> Result<Self, SeatError> {
+        for distance in [left_cm, right_cm] {
+            if !(20.0..=400.0).contains(&distance) {
+                return Err(SeatError::DistanceOutOfRange(distance));
+            }
+        }
+        if !(0.0..=200.0).contains(&width_percent) {
+            return Err(SeatError::WidthOutOfRange(width_percent));
+        }
+        Ok(Self {
+            left_cm,
+            right_cm,
+            width_percent,
+        })
+    }
+
+    // Задержка (мс) для левого и правого канала: ближний динамик ждёт дальний
+    pub fn delays_ms(&self) -> (f32, f32) {
+        let diff = (self.right_cm - self.left_cm) / SOUND_CM_PER_MS;
+        if diff > 0.0 {
+            (diff, 0.0)
+        } else {
+            (0.0, -diff)
+        }
+    }
+
+    // Усиление (дБ) для левого и правого канала: ближний приглушаем
+    pub fn gains_db(&self) -> (f32, f32) {
+        let diff =
+            (20.0 * (self.right_cm / self.left_cm).log10()).clamp(-MAX_BALANCE_DB, MAX_BALANCE_DB);
+        if diff > 0.0 {
+            (-diff, 0.0)
+        } else {
+            (0.0, diff)
+        }
+    }
+}
+
+// Тот же формат, что после /seat: "левый правый [ширина%]"
+impl fmt::Display for DriverSeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.left_cm, self.right_cm, self.width_percent
+        )
+    }
+}
+
+impl FromStr for DriverSeat {
+    type Err = SeatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split_whitespace()
+            .map(|token| {
+                token
+                    .trim_end_matches('%')
+                    .parse::<f32>()
+                    .map_err(|_| SeatError::InvalidNumber(token.to_string()))
+            })
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        match numbers.as_slice() {
+            [left, right] => DriverSeat::new(*left, *right, 100.0),
+            [left, right, width] => DriverSeat::new(*left, *right, *width),
+            _ => Err(SeatError::MissingDistance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearer_left_speaker_is_delayed_and_attenuated() {
+        let seat: DriverSeat = "70 140".parse().unwrap();
+        let (left_delay, right_delay) = seat.delays_ms();
+        let (left_gain, right_gain) = seat.gains_db();
+
+        assert!((left_delay - 70.0 / SOUND_CM_PER_MS).abs() < 1e-4);
+        assert_eq!(right_delay, 0.0);
+        assert!((left_gain + 6.0).abs() < 0.05);
+        assert_eq!(right_gain, 0.0);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!("90".parse::<DriverSeat>(), Err(SeatError::MissingDistance));
+        assert_eq!(
+            "5 90".parse::<DriverSeat>(),
+            Err(SeatError::DistanceOutOfRange(5.0))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+use std::f32::consts::PI;
+
+// Окно Ханна заданной длины
+pub fn hann(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / len as f32).cos())
+        .collect()
+}
+
+// Кратковременный спектр: для каждого кадра вызывает f(энергия кадра, спектр мощности).
+// Спектр содержит frame / 2 + 1 бинов, шаг бина = sample_rate / frame.
+pub fn for_each_power_spectrum(
+    samples: &[f32],
+    frame: usize,
+    hop: usize,
+    mut f: impl FnMut(f32, &[f32]),
+) {
+    if samples.len() < frame || frame == 0 || hop == 0 {
+        return;
+    }
+
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(frame);
+    let window = hann(frame);
+    let mut buffer = vec![Complex::new(0.0, 0.0); frame];
+    let mut power = vec![0.0; frame / 2 + 1];
+
+    for start in (0..=samples.len() - frame).step_by(hop) {
+        let chunk = &samples[start..start + frame];
+        let energy = chunk.iter().map(|s| s * s).sum::<f32>() / frame as f32;
+
+        for ((dst, sample), w) in buffer.iter_mut().zip(chunk).zip(&window) {
+            *dst = Complex::new(sample * w, 0.0);
+        }
+        fft.process(&mut buffer);
+
+        for (p, c) in power.iter_mut().zip(&buffer) {
+            *p = c.norm_sqr();
+        }
+        f(energy, &power);
+    }
+}
+
+// Мощность в дБ с защитой от логарифма нуля
+pub fn to_db(power: f32) -> f32 {
+    10.0 * power.max(1e-12).log10()
+}
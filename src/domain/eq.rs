@@ -0,0 +1,176 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const MAX_EQ_BANDS: usize = 10;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EqError {
+    #[error("Не понял «{0}». Полоса пишется как частота:усиление:Q, например 60:4:0.7")]
+    InvalidToken(String),
+
+    #[error("Частота {0} Гц вне диапазона 20–20000")]
+    FrequencyOutOfRange(f32),
+
+    #[error("Усиление {0} дБ вне диапазона -24…+24")]
+    GainOutOfRange(f32),
+
+    #[error("Q {0} вне диапазона 0.1–10")]
+    QOutOfRange(f32),
+
+    #[error("Предусиление {0} дБ вне диапазона -24…+12")]
+    PreampOutOfRange(f32),
+
+    #[error("Громкость {0} LUFS вне диапазона -30…-5")]
+    LoudnessOutOfRange(f32),
+
+    #[error("Нужна хотя бы одна полоса (максимум {MAX_EQ_BANDS})")]
+    BandCount,
+}
+
+// Одна полоса параметрического эквалайзера
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqBand {
+    pub freq: f32,
+    pub gain: f32,
+    pub q: f32,
+}
+
+impl EqBand {
+    pub fn new(freq: f32, gain: f32, q: f32) -> Result<Self, EqError> {
+        if !(20.0..=20000.0).contains(&freq) {
+            return Err(EqError::FrequencyOutOfRange(freq));
+        }
+        if !(-24.0..=24.0).contains(&gain) {
+            return Err(EqError::GainOutOfRange(gain));
+        }
+        if !(0.1..=10.0).contains(&q) {
+            return Err(EqError::QOutOfRange(q));
+        }
+        Ok(Self { freq, gain, q })
+    }
+}
+
+impl fmt::Display for EqBand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.freq, self.gain, self.q)
+    }
+}
+
+// "freq:gain[:q]", Q по умолчанию 1.0
+impl FromStr for EqBand {
+    type Err = EqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || EqError::InvalidToken(s.to_string());
+        let mut parts = s.split(':');
+
+        let freq = parse_number(parts.next(), s)?;
+        let gain = parse_number(parts.next(), s)?;
+        let q = match parts.next() {
+            Some(raw) => parse_number(Some(raw), s)?,
+            None => 1.0,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        EqBand::new(freq, gain, q)
+    }
+}
+
+// Пользовательский пресет: полосы + предусиление + целевая громкость
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomEq {
+    pub bands: Vec<EqBand>,
+    pub preamp: f32,
+    pub loudness: f32,
+}
+
+impl CustomEq {
+    pub fn new(bands: Vec<EqBand>, preamp: f32, loudness: f32) -> Result<Self, EqError> {
+        if bands.is_empty() || bands.len() > MAX_EQ_BANDS {
+            return Err(EqError::BandCount);
+        }
+        if !(-24.0..=12.0).contains(&preamp) {
+            return Err(EqError::PreampOutOfRange(preamp));
+        }
+        if !(-30.0..=-5.0).contains(&loudness) {
+            return Err(EqError::LoudnessOutOfRange(loudness));
+        }
+        Ok(Self {
+            bands,
+            preamp,
+            loudness,
+        })
+    }
+}
+
+// Тот же формат, что пользователь вводит после /eq — он же хранится в БД
+impl fmt::Display for CustomEq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for band in &self.bands {
+            write!(f, "{} ", band)?;
+        }
+        write!(f, "preamp={} lufs={}", self.preamp, self.loudness)
+    }
+}
+
+// "60:4:0.7 250:-2 4000:3:1.4 preamp=-3 lufs=-14"
+impl FromStr for CustomEq {
+    type Err = EqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bands = Vec::new();
+        let mut preamp = 0.0;
+        let mut loudness = -14.0;
+
+        for token in s.split_whitespace() {
+            if let Some(raw) = token.strip_prefix("preamp=") {
+                preamp = parse_number(Some(raw), token)?;
+            } else if let Some(raw) = token.strip_prefix("lufs=") {
+                loudness = parse_number(Some(raw), token)?;
+            } else {
+                bands.push(token.parse()?);
+            }
+        }
+
+        CustomEq::new(bands, preamp, loudness)
+    }
+}
+
+fn parse_number(raw: Option<&str>, token: &str) -> Result<f32, EqError> {
+    raw.and_then(|v| v.trim_start_matches('+').parse::<f32>().ok())
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| EqError::InvalidToken(token.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bands_and_options() {
+        let eq: CustomEq = "60:+4:0.7 250:-2 preamp=-3 lufs=-12".parse().unwrap();
+        assert_eq!(eq.bands.len(), 2);
+        assert_eq!(eq.bands[1], EqBand::new(250.0, -2.0, 1.0).unwrap());
+        assert_eq!(eq.preamp, -3.0);
+        assert_eq!(eq.loudness, -12.0);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let eq: CustomEq = "60:4:0.7 8000:2.5:1.4 preamp=-2".parse().unwrap();
+        assert_eq!(eq.to_string().parse::<CustomEq>().unwrap(), eq);
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert_eq!(
+            "5:4".parse::<CustomEq>(),
+            Err(EqError::FrequencyOutOfRange(5.0))
+        );
+        assert_eq!("preamp=-3".parse::<CustomEq>(), Err(EqError::BandCount));
+        assert!("60:4:abc".parse::<CustomEq>().is_err());
+    }
+}
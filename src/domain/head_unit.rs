@@ -0,0 +1,180 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Длиннее многие магнитолы не показывают, а некоторые не открывают вовсе
+pub const MAX_FILE_NAME_CHARS: usize = 64;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HeadUnitError {
+    #[error("Не понял «{0}» — нужен размер обложки в пикселях")]
+    InvalidNumber(String),
+
+    #[error("Размер обложки {0} px вне диапазона 100–1000")]
+    CoverSizeOutOfRange(u32),
+}
+
+// Режим совместимости со штатными магнитолами: ID3v2.3 + ID3v1,
+// маленькая обложка, MP3 44.1 кГц CBR и латиница в именах файлов
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadUnitMode {
+    // Максимальная сторона обложки, px
+    pub cover_px: u32,
+}
+
+impl Default for HeadUnitMode {
+    fn default() -> Self {
+        Self { cover_px: 300 }
+    }
+}
+
+impl HeadUnitMode {
+    pub fn new(cover_px: u32) -> Result<Self, HeadUnitError> {
+        if !(100..=1000).contains(&cover_px) {
+            return Err(HeadUnitError::CoverSizeOutOfRange(cover_px));
+        }
+        Ok(Self { cover_px })
+    }
+}
+
+// Тот же формат, что после /headunit: размер обложки
+impl fmt::Display for HeadUnitMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cover_px)
+    }
+}
+
+impl FromStr for HeadUnitMode {
+    type Err = HeadUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Ok(Self::default()),
+            token => {
+                let px = token
+                    .trim_end_matches("px")
+                    .parse()
+                    .map_err(|_| HeadUnitError::InvalidNumber(token.to_string()))?;
+                HeadUnitMode::new(px)
+            }
+        }
+    }
+}
+
+fn cyrillic_to_latin(c: char) -> Option<&'static str> {
+    let latin = match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'ґ' => "g",
+        'д' => "d",
+        'е' => "e",
+        'ё' => "yo",
+        'є' => "ye",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'і' => "i",
+        'ї' => "yi",
+        'й' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ў' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+// Кириллица латиницей; остальное не-ASCII (эмодзи, иероглифы) выбрасываем
+pub fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let Some(latin) = cyrillic_to_latin(lower) else {
+            continue;
+        };
+        if lower != c {
+            // Заглавная: "Щ" -> "Shch"
+            let mut chars = latin.chars();
+            if let Some(first) = chars.next() {
+                out.push(first.to_ascii_uppercase());
+                out.push_str(chars.as_str());
+            }
+        } else {
+            out.push_str(latin);
+        }
+    }
+    out
+}
+
+// Имя файла, которое переварит любая магнитола: латиница, без спецсимволов,
+// не длиннее MAX_FILE_NAME_CHARS вместе с расширением
+pub fn safe_file_name(stem: &str, extension: &str) -> String {
+    let cleaned: String = transliterate(stem)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || " -_().,'&".contains(c) {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut stem = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let max_stem = MAX_FILE_NAME_CHARS.saturating_sub(extension.len() + 1);
+    stem.truncate(max_stem);
+    let stem = stem.trim_end_matches([' ', '-', '.', ',']);
+
+    let stem = if stem.is_empty() { "track" } else { stem };
+    format!("{}.{}", stem, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterates_cyrillic() {
+        assert_eq!(transliterate("Щедрик — Кино"), "Shchedrik  Kino");
+        assert_eq!(transliterate("Ёлка 🎄 Їжак"), "Yolka  Yizhak");
+    }
+
+    #[test]
+    fn file_names_are_ascii_and_short() {
+        assert_eq!(
+            safe_file_name("Звезда по имени Солнце", "mp3"),
+            "Zvezda po imeni Solntse.mp3"
+        );
+        assert_eq!(safe_file_name("🔥🔥", "mp3"), "track.mp3");
+
+        let long = safe_file_name(&"Очень длинное название ".repeat(10), "mp3");
+        assert!(long.len() <= MAX_FILE_NAME_CHARS);
+        assert!(long.is_ascii());
+        assert!(long.ends_with(".mp3"));
+    }
+}
@@ -0,0 +1,70 @@
+use crate::domain::output_format::OutputFormat;
+use crate::domain::provenance::Provenance;
+use crate::domain::tag_edits::TagEdits;
+use async_trait::async_trait;
+
+// Откуда берём звук
+#[derive(Debug, Clone)]
+pub enum JobSource {
+    // Ссылка на YouTube
+    Link(String),
+    // Файл, присланный в чат; скачиваем при запуске
+    Upload { file_id: String, file_name: String },
+}
+
+// Задание между присылкой ссылки и запуском обработки:
+// пользователь набирает эффекты кнопками, и только потом жмёт "Погнали"
+#[derive(Debug, Clone)]
+pub struct PendingJob {
+    pub user_id: i64,
+    pub source: JobSource,
+    // Ключи выбранных пресетов из каталога
+    pub presets: Vec<String>,
+    // Формат файла; по умолчанию — из настроек пользователя (/format)
+    pub format: OutputFormat,
+    // Повторный запуск в моно-безопасном варианте
+    pub mono_safe: bool,
+    // Обложка и теги от пользователя (шаг «✏ Теги»)
+    pub tags: TagEdits,
+    // Ждём от пользователя фото или строки с тегами для этого задания
+    pub editing_tags: bool,
+    // Файл уже обрабатывали в боте (теги CARBOT_* в присланном файле)
+    pub previous: Option<Provenance>,
+}
+
+impl PendingJob {
+    pub fn new(user_id: i64, source: JobSource, format: OutputFormat) -> Self {
+        Self {
+            user_id,
+            source,
+            presets: Vec::new(),
+            format,
+            mono_safe: false,
+            tags: TagEdits::default(),
+            editing_tags: false,
+            previous: None,
+        }
+    }
+}
+
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    // Сохраняет задание и возвращает его короткий id (влезает в callback-данные)
+    async fn create(&self, job: PendingJob) -> String;
+
+    async fn get(&self, job_id: &str) -> Option<PendingJob>;
+
+    async fn update(&self, job_id: &str, job: PendingJob);
+
+    async fn remove(&self, job_id: &str) -> Option<PendingJob>;
+
+    // Возвращает забранное через remove задание под тем же id
+    async fn restore(&self, job_id: &str, job: PendingJob);
+
+    // Последнее задание пользователя, в котором он сейчас правит теги
+    async fn find_editing(&self, user_id: i64) -> Option<(String, PendingJob)>;
+
+    // Закрывает шаг «✏ Теги» во всех заданиях пользователя: фото и текст
+    // дальше не должны уходить в старое задание
+    async fn stop_editing(&self, user_id: i64);
+}
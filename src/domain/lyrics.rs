@@ -0,0 +1,205 @@
+// Строка текста песни с моментом начала
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricLine {
+    pub start_ms: u32,
+    pub text: String,
+}
+
+// Текст песни из субтитров ролика
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lyrics {
+    // Язык субтитров как у YouTube: "ru", "en-US"
+    pub language: String,
+    pub lines: Vec<LyricLine>,
+}
+
+impl Lyrics {
+    // Для USLT и тега LYRICS: просто строки подряд
+    pub fn plain_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Код языка для ID3 (ISO 639-2); незнакомые — "XXX", как велит стандарт
+    pub fn id3_language(&self) -> &'static str {
+        let base = self.language.split(['-', '_']).next().unwrap_or("");
+        match base.to_lowercase().as_str() {
+            "ru" => "rus",
+            "uk" => "ukr",
+            "be" => "bel",
+            "kk" => "kaz",
+            "en" => "eng",
+            "de" => "deu",
+            "fr" => "fra",
+            "es" => "spa",
+            "it" => "ita",
+            "pt" => "por",
+            "tr" => "tur",
+            "pl" => "pol",
+            "ja" => "jpn",
+            "ko" => "kor",
+            _ => "XXX",
+        }
+    }
+
+    // Подгоняет время строк под готовый трек: начало срезано /trim,
+    // а slowed/nightcore растягивают или сжимают всё остальное
+    pub fn retimed(&self, start_s: f32, end_s: f32, tempo: f32) -> Lyrics {
+        let lines = self
+            .lines
+            .iter()
+            .filter(|line| (line.start_ms as f32 / 1000.0) < end_s)
+            .map(|line| {
+                let at = (line.start_ms as f32 / 1000.0 - start_s).max(0.0) / tempo;
+                LyricLine {
+                    start_ms: (at * 1000.0).round() as u32,
+                    text: line.text.clone(),
+                }
+            })
+            .collect();
+        Lyrics {
+            language: self.language.clone(),
+            lines,
+        }
+    }
+}
+
+// Какие субтитры брать: на языке трека, а если язык неизвестен —
+// единственную ручную дорожку. Автосубтитры сюда не попадают вовсе
+pub fn pick_subtitle_language(track_language: Option<&str>, available: &[&str]) -> Option<String> {
+    let base = |lang: &str| lang.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    match track_language {
+        Some(language) => {
+            let wanted = base(language);
+            available
+                .iter()
+                .find(|lang| lang.eq_ignore_ascii_case(language))
+                .or_else(|| available.iter().find(|lang| base(lang) == wanted))
+                .map(|lang| lang.to_string())
+        }
+        None => match available {
+            [only] => Some(only.to_string()),
+            _ => None,
+        },
+    }
+}
+
+// WebVTT от YouTube: блоки "00:01:02.345 --> 00:01:04.000" и текст под ними.
+// Теги разметки и ноты выбрасываем, повторы подряд склеиваем
+pub fn parse_webvtt(vtt: &str) -> Vec<LyricLine> {
+    let vtt = vtt.replace("\r\n", "\n");
+    let mut lines: Vec<LyricLine> = Vec::new();
+    let mut blocks = vtt.split("\n\n").map(|b| b.trim_matches('\n'));
+
+    // Первый блок — заголовок WEBVTT
+    blocks.next();
+    for block in blocks {
+        let mut rows = block.lines();
+        let Some(start_ms) = rows.by_ref().find_map(|row| {
+            row.split_once("-->")
+                .and_then(|(start, _)| parse_timestamp(start))
+        }) else {
+            continue;
+        };
+
+        let text = rows
+            .map(strip_markup)
+            .filter(|row| !row.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() || lines.last().is_some_and(|last| last.text == text) {
+            continue;
+        }
+        lines.push(LyricLine { start_ms, text });
+    }
+    lines
+}
+
+// "01:02.345" или "00:01:02.345" -> миллисекунды
+fn parse_timestamp(text: &str) -> Option<u32> {
+    let text = text.trim();
+    let (clock, millis) = text.split_once('.')?;
+    let mut seconds: u32 = 0;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u32>().ok()?;
+    }
+    Some(seconds * 1000 + millis.get(..3)?.parse::<u32>().ok()?)
+}
+
+fn strip_markup(row: &str) -> String {
+    let mut text = String::with_capacity(row.len());
+    let mut in_tag = false;
+    for c in row.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            '♪' | '♫' if !in_tag => {}
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&amp;", "&")
+        .replace("&nbsp;", " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_youtube_webvtt() {
+        let vtt = "WEBVTT\nKind: captions\nLanguage: ru\n\n\
+            00:00:12.500 --> 00:00:15.000\n♪ Группа крови на рукаве ♪\n\n\
+            00:00:15.000 --> 00:00:17.000 align:start\n<c>Мой порядковый</c> номер\nна рукаве\n\n\
+            00:00:17.000 --> 00:00:18.000\nМой порядковый номер на рукаве\n\n\
+            00:00:20.000 --> 00:00:21.000\n♪♪\n";
+        assert_eq!(
+            parse_webvtt(vtt),
+            [
+                LyricLine {
+                    start_ms: 12_500,
+                    text: "Группа крови на рукаве".into()
+                },
+                LyricLine {
+                    start_ms: 15_000,
+                    text: "Мой порядковый номер на рукаве".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn picks_track_language_and_retimes() {
+        assert_eq!(
+            pick_subtitle_language(Some("ru"), &["en", "ru-RU"]),
+            Some("ru-RU".into())
+        );
+        assert_eq!(pick_subtitle_language(None, &["en", "ru"]), None);
+        assert_eq!(pick_subtitle_language(None, &["en"]), Some("en".into()));
+
+        let lyrics = Lyrics {
+            language: "ru".into(),
+            lines: vec![
+                LyricLine {
+                    start_ms: 1_000,
+                    text: "до обрезки".into(),
+                },
+                LyricLine {
+                    start_ms: 10_000,
+                    text: "куплет".into(),
+                },
+            ],
+        };
+        // Срезали 2 с тишины и замедлили до 0.8
+        let retimed = lyrics.retimed(2.0, 200.0, 0.8);
+        assert_eq!(retimed.lines[0].start_ms, 0);
+        assert_eq!(retimed.lines[1].start_ms, 10_000);
+        assert_eq!(lyrics.id3_language(), "rus");
+    }
+}
@@ -1,2 +1,22 @@
 pub mod audio_service;
+pub mod audio_upload;
+pub mod calibration;
+pub mod cover_art;
+pub mod driver_seat;
+pub mod dsp;
+pub mod eq;
+pub mod head_unit;
+pub mod job_repository;
+pub mod lyrics;
+pub mod mono_check;
+pub mod music_analysis;
+pub mod output_format;
+pub mod preset_catalog;
+pub mod provenance;
+pub mod settings_repository;
+pub mod tag_edits;
+pub mod title_rules;
+pub mod track_edges;
+pub mod track_name;
+pub mod track_tags;
 pub mod user_repository;
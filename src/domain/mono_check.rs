@@ -0,0 +1,89 @@
+// Сколько может потерять трек при сведении в моно, прежде чем мы предупредим.
+// 3 дБ теряют просто независимые каналы — обычное широкое стерео. Звук гасит
+// сам себя только при отрицательной корреляции: 4.5 дБ — это около -0.3
+const MAX_MONO_LOSS_DB: f32 = 4.5;
+
+// Совместимость с моно (одна BT-колонка, моно-AUX)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonoCheck {
+    // Корреляция каналов: 1 — чистое моно, 0 — независимые, -1 — противофаза
+    pub correlation: f32,
+    // Насколько тише становится трек при сложении каналов, дБ
+    pub mono_loss_db: f32,
+}
+
+impl MonoCheck {
+    pub fn loses_content(&self) -> bool {
+        self.correlation < 0.0 && self.mono_loss_db > MAX_MONO_LOSS_DB
+    }
+}
+
+// Копит суммы по ходу декодирования, чтобы не держать весь трек в памяти
+#[derive(Debug, Default)]
+pub struct CorrelationMeter {
+    ll: f64,
+    rr: f64,
+    lr: f64,
+}
+
+impl CorrelationMeter {
+    pub fn push(&mut self, left: f32, right: f32) {
+        let (l, r) = (left as f64, right as f64);
+        self.ll += l * l;
+        self.rr += r * r;
+        self.lr += l * r;
+    }
+
+    pub fn finish(&self) -> MonoCheck {
+        let stereo = self.ll + self.rr;
+        if stereo <= f64::EPSILON {
+            return MonoCheck {
+                correlation: 1.0,
+                mono_loss_db: 0.0,
+            };
+        }
+
+        let correlation = self.lr / (self.ll * self.rr).sqrt().max(f64::EPSILON);
+        // Мощность (L+R)/2 относительно средней мощности каналов
+        let mono = (stereo + 2.0 * self.lr) / (2.0 * stereo);
+        let mono_loss_db = -10.0 * mono.max(1e-6).log10();
+
+        MonoCheck {
+            correlation: correlation.clamp(-1.0, 1.0) as f32,
+            mono_loss_db: mono_loss_db as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure(signal: impl Fn(f32) -> (f32, f32)) -> MonoCheck {
+        let mut meter = CorrelationMeter::default();
+        for i in 0..4800 {
+            let (l, r) = signal(i as f32 / 48000.0);
+            meter.push(l, r);
+        }
+        meter.finish()
+    }
+
+    #[test]
+    fn detects_out_of_phase_content() {
+        let tone = |t: f32| (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+
+        let mono = measure(|t| (tone(t), tone(t)));
+        assert!((mono.correlation - 1.0).abs() < 1e-3);
+        assert!(mono.mono_loss_db.abs() < 0.01);
+        assert!(!mono.loses_content());
+
+        // Разные инструменты в каналах — широко, но в моно ничего не гаснет
+        let wide = measure(|t| (tone(t), (2.0 * std::f32::consts::PI * 550.0 * t).sin()));
+        assert!(wide.correlation.abs() < 0.05);
+        assert!(!wide.loses_content());
+
+        let inverted = measure(|t| (tone(t), -0.8 * tone(t)));
+        assert!(inverted.correlation < -0.99);
+        assert!(inverted.loses_content());
+    }
+}
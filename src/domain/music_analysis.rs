@@ -0,0 +1,245 @@
+use crate::domain::dsp::for_each_power_spectrum;
+use std::fmt;
+
+// Частота, на которой анализируем: ноты до ~5 кГц и атаки ударных ещё видны
+pub const ANALYSIS_RATE: u32 = 11025;
+
+// Кадры для огибающей атак: ~86 кадров в секунду
+const ONSET_FRAME: usize = 1024;
+const ONSET_HOP: usize = 128;
+
+// Кадры для хромы: бин ~2.7 Гц, чтобы различать полутоны от ~100 Гц
+const CHROMA_FRAME: usize = 4096;
+const CHROMA_HOP: usize = 2048;
+const CHROMA_MIN_HZ: f32 = 100.0;
+const CHROMA_MAX_HZ: f32 = 2000.0;
+
+// Диапазон поиска темпа и темп, к которому тянет слушателя при двойной трактовке
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+const PREFERRED_BPM: f32 = 120.0;
+
+// Профили тональностей Крумхансла — Кесслер, от тоники по полутонам
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+// Так ноты пишут диджеи и TKEY (в ID3 допустимы только "#" и "b")
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MusicalKey {
+    // Тоника: 0 — до, 9 — ля
+    pub tonic: u8,
+    pub minor: bool,
+}
+
+impl MusicalKey {
+    // Тональность после сдвига высоты (nightcore, slowed)
+    pub fn transposed(&self, semitones: i32) -> MusicalKey {
+        MusicalKey {
+            tonic: (self.tonic as i32 + semitones).rem_euclid(12) as u8,
+            minor: self.minor,
+        }
+    }
+
+    // Колесо Camelot: соседние номера сводятся без диссонанса
+    pub fn camelot(&self) -> String {
+        // У минора номер как у параллельного мажора (Am = C = 8)
+        let major_tonic = if self.minor {
+            (self.tonic as u32 + 3) % 12
+        } else {
+            self.tonic as u32
+        };
+        let number = (major_tonic * 7 % 12 + 7) % 12 + 1;
+        format!("{}{}", number, if self.minor { 'A' } else { 'B' })
+    }
+}
+
+// Как в TKEY: "F#m", "Bb"
+impl fmt::Display for MusicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            NOTE_NAMES[self.tonic as usize % 12],
+            if self.minor { "m" } else { "" }
+        )
+    }
+}
+
+// Темп по автокорреляции огибающей атак (spectral flux)
+pub fn estimate_bpm(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let mut envelope: Vec<f32> = Vec::new();
+    let mut previous: Vec<f32> = Vec::new();
+    for_each_power_spectrum(samples, ONSET_FRAME, ONSET_HOP, |_, power| {
+        // Логарифм уравнивает тихие и громкие атаки
+        let current: Vec<f32> = power
+            .iter()
+            .map(|p| (1.0 + 100.0 * p.sqrt()).ln())
+            .collect();
+        let flux = if previous.is_empty() {
+            0.0
+        } else {
+            current
+                .iter()
+                .zip(&previous)
+                .map(|(now, before)| (now - before).max(0.0))
+                .sum()
+        };
+        envelope.push(flux);
+        previous = current;
+    });
+
+    let frame_rate = sample_rate as f32 / ONSET_HOP as f32;
+    // Вычитаем скользящее среднее (~0.5 с): остаются только всплески
+    let half = (frame_rate * 0.25) as usize;
+    let onsets: Vec<f32> = (0..envelope.len())
+        .map(|i| {
+            let window = &envelope[i.saturating_sub(half)..(i + half + 1).min(envelope.len())];
+            let mean = window.iter().sum::<f32>() / window.len() as f32;
+            (envelope[i] - mean).max(0.0)
+        })
+        .collect();
+
+    let min_lag = (60.0 * frame_rate / MAX_BPM).floor() as usize;
+    let max_lag = (60.0 * frame_rate / MIN_BPM).ceil() as usize;
+    if onsets.len() < max_lag * 4 {
+        return None;
+    }
+
+    let scores: Vec<f32> = (min_lag..=max_lag + 1)
+        .map(|lag| {
+            let sum: f32 = onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum();
+            let bpm = 60.0 * frame_rate / lag as f32;
+            // Между 64 и 128 выбираем то, что ближе к привычным 120
+            let octaves = (bpm / PREFERRED_BPM).log2();
+            sum / (onsets.len() - lag) as f32 * (-0.5 * octaves * octaves).exp()
+        })
+        .collect();
+
+    let best = (1..scores.len() - 1).max_by(|&a, &b| scores[a].total_cmp(&scores[b]))?;
+    if scores[best] <= 0.0 {
+        return None;
+    }
+    // Параболой между соседними лагами — точнее целого числа кадров
+    let (left, mid, right) = (scores[best - 1], scores[best], scores[best + 1]);
+    let denom = left - 2.0 * mid + right;
+    let offset = if denom.abs() > f32::EPSILON {
+        (0.5 * (left - right) / denom).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let lag = (min_lag + best) as f32 + offset;
+    Some(60.0 * frame_rate / lag)
+}
+
+// Тональность: хрома (энергия по 12 классам нот) против профилей Крумхансла
+pub fn estimate_key(samples: &[f32], sample_rate: u32) -> Option<MusicalKey> {
+    let bin_hz = sample_rate as f32 / CHROMA_FRAME as f32;
+    let mut chroma = [0.0f32; 12];
+    for_each_power_spectrum(samples, CHROMA_FRAME, CHROMA_HOP, |energy, power| {
+        if energy < 1e-6 {
+            return;
+        }
+        let mut frame = [0.0f32; 12];
+        for (bin, p) in power.iter().enumerate() {
+            let freq = bin as f32 * bin_hz;
+            if !(CHROMA_MIN_HZ..=CHROMA_MAX_HZ).contains(&freq) {
+                continue;
+            }
+            let midi = 69.0 + 12.0 * (freq / 440.0).log2();
+            frame[(midi.round() as i32).rem_euclid(12) as usize] += p.sqrt();
+        }
+        // Каждый кадр с равным весом, иначе всё решат громкие припевы
+        let total: f32 = frame.iter().sum();
+        if total > 0.0 {
+            for (sum, value) in chroma.iter_mut().zip(frame) {
+                *sum += value / total;
+            }
+        }
+    });
+    if chroma.iter().all(|c| *c == 0.0) {
+        return None;
+    }
+
+    (0..12u8)
+        .flat_map(|tonic| [(tonic, false), (tonic, true)])
+        .map(|(tonic, minor)| {
+            let profile = if minor {
+                &MINOR_PROFILE
+            } else {
+                &MAJOR_PROFILE
+            };
+            let rotated: Vec<f32> = (0..12)
+                .map(|pc| profile[(pc + 12 - tonic as usize) % 12])
+                .collect();
+            (MusicalKey { tonic, minor }, correlation(&chroma, &rotated))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(key, _)| key)
+}
+
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    cov / (var_a * var_b).sqrt().max(f32::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const RATE: f32 = ANALYSIS_RATE as f32;
+
+    #[test]
+    fn finds_tempo_of_click_track() {
+        let beat = 60.0 / 128.0;
+        let samples: Vec<f32> = (0..(RATE * 30.0) as usize)
+            .map(|i| {
+                let t = i as f32 / RATE;
+                let since_beat = t % beat;
+                // Короткий щелчок 1 кГц с затуханием
+                (2.0 * PI * 1000.0 * t).sin() * (-since_beat * 60.0).exp()
+            })
+            .collect();
+        let bpm = estimate_bpm(&samples, ANALYSIS_RATE).unwrap();
+        assert!((bpm - 128.0).abs() < 1.5, "{bpm}");
+    }
+
+    #[test]
+    fn finds_minor_key_of_progression() {
+        // Am — Dm — E — Am, по две секунды на аккорд
+        let chords: [&[f32]; 4] = [
+            &[220.0, 261.63, 329.63],
+            &[293.66, 349.23, 440.0],
+            &[329.63, 415.30, 493.88],
+            &[220.0, 261.63, 329.63],
+        ];
+        let samples: Vec<f32> = (0..(RATE * 16.0) as usize)
+            .map(|i| {
+                let t = i as f32 / RATE;
+                let chord = chords[(t / 2.0) as usize % chords.len()];
+                chord.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() / 3.0
+            })
+            .collect();
+        let key = estimate_key(&samples, ANALYSIS_RATE).unwrap();
+        assert_eq!(key.to_string(), "Am");
+        assert_eq!(key.camelot(), "8A");
+        assert_eq!(key.transposed(4).to_string(), "C#m");
+    }
+}
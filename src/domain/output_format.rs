@@ -0,0 +1,109 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Формат «{0}» не поддерживается: mp3, mp3vbr, aac, alac, opus, flac или wav")]
+pub struct UnknownFormat(pub String);
+
+// В чём отдаём стерео-файлы (5.1 кодируется отдельно, см. MultichannelCodec)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    // MP3 320 кбит/с — играет везде
+    #[default]
+    Mp3Cbr,
+    // MP3 VBR V0: то же качество, файл меньше, но старые магнитолы врут с длительностью
+    Mp3Vbr,
+    Aac,
+    // Apple Lossless в M4A — без потерь для iPhone/CarPlay
+    Alac,
+    Opus,
+    Flac,
+    Wav,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 7] = [
+        OutputFormat::Mp3Cbr,
+        OutputFormat::Mp3Vbr,
+        OutputFormat::Aac,
+        OutputFormat::Alac,
+        OutputFormat::Opus,
+        OutputFormat::Flac,
+        OutputFormat::Wav,
+    ];
+
+    // Ключ для команды /format и callback-данных
+    pub fn key(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp3Cbr => "mp3",
+            OutputFormat::Mp3Vbr => "mp3vbr",
+            OutputFormat::Aac => "aac",
+            OutputFormat::Alac => "alac",
+            OutputFormat::Opus => "opus",
+            OutputFormat::Flac => "flac",
+            OutputFormat::Wav => "wav",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp3Cbr => "MP3 320",
+            OutputFormat::Mp3Vbr => "MP3 VBR",
+            OutputFormat::Aac => "AAC",
+            OutputFormat::Alac => "ALAC",
+            OutputFormat::Opus => "Opus",
+            OutputFormat::Flac => "FLAC",
+            OutputFormat::Wav => "WAV",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp3Cbr | OutputFormat::Mp3Vbr => "mp3",
+            OutputFormat::Aac | OutputFormat::Alac => "m4a",
+            OutputFormat::Opus => "opus",
+            OutputFormat::Flac => "flac",
+            OutputFormat::Wav => "wav",
+        }
+    }
+
+    // Следующий формат по кругу — для кнопки на клавиатуре задания
+    pub fn next(&self) -> OutputFormat {
+        let pos = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(pos + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| f.key() == key)
+            .ok_or(UnknownFormat(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys_and_cycles() {
+        for format in OutputFormat::ALL {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        assert_eq!(" FLAC ".parse(), Ok(OutputFormat::Flac));
+        assert!("ogg".parse::<OutputFormat>().is_err());
+        assert_eq!(OutputFormat::Wav.next(), OutputFormat::Mp3Cbr);
+    }
+}
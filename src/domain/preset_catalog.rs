@@ -0,0 +1,387 @@
+use crate::domain::audio_service::{
+    AudioPreset, CleanupStrength, HighwayStrength, PresetStage, ReverbParams, Room, TempoParams,
+    VirtualBassParams, VocalMode,
+};
+use crate::domain::settings_repository::UserSettings;
+
+// Пресет, который можно выбрать кнопкой: ключ для callback-данных,
+// подпись, занимаемые этапы цепочки и цена в кредитах.
+// Готовые комбо (Slowed + Reverb) занимают несколько этапов сразу.
+pub struct PresetEntry {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub stages: &'static [PresetStage],
+    pub cost: i32,
+    // Поднимает уровень или бас: второй такой проход поверх первого даёт
+    // перегруз и гул, а не "ещё лучше"
+    pub boost: bool,
+    // None — пресет недоступен пользователю (например, не настроен /eq)
+    pub build: fn(&UserSettings) -> Option<Vec<AudioPreset>>,
+}
+
+// Реверб для slowed-комбо: поменьше "комнаты", чтобы не размыть вокал
+const SLOWED_REVERB: ReverbParams = ReverbParams {
+    room: Room::Plate,
+    mix: 0.3,
+};
+
+// Пресет комнаты: долю «мокрого» сигнала задаёт пользователь (/reverb),
+// иначе — своя для каждой комнаты
+fn room_preset(settings: &UserSettings, room: Room, default_mix: f32) -> AudioPreset {
+    AudioPreset::Reverb(ReverbParams {
+        room,
+        mix: settings.reverb_mix.unwrap_or(default_mix),
+    })
+}
+
+pub const CATALOG: &[PresetEntry] = &[
+    PresetEntry {
+        key: "bass",
+        label: "🏎 Car Bass",
+        stages: &[PresetStage::Tone],
+        cost: 1,
+        boost: true,
+        build: |_| Some(vec![AudioPreset::CarBass]),
+    },
+    PresetEntry {
+        key: "hifi",
+        label: "🎧 Pure Hi-Fi",
+        stages: &[PresetStage::Tone],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::PureHiFi]),
+    },
+    PresetEntry {
+        key: "extreme",
+        label: "🔥 Extreme Low",
+        stages: &[PresetStage::Tone],
+        cost: 1,
+        boost: true,
+        build: |_| Some(vec![AudioPreset::ExtremeLow]),
+    },
+    PresetEntry {
+        key: "stock",
+        label: "📻 Штатка",
+        stages: &[PresetStage::Tone],
+        cost: 1,
+        boost: true,
+        build: |s| {
+            Some(vec![AudioPreset::StockSpeakers(
+                s.speaker_size_cm
+                    .map(VirtualBassParams::for_speaker_size)
+                    .unwrap_or_default(),
+            )])
+        },
+    },
+    PresetEntry {
+        key: "custom",
+        label: "⭐ Мой пресет",
+        stages: &[PresetStage::Tone],
+        cost: 1,
+        boost: true,
+        build: |s| s.custom_eq.clone().map(|eq| vec![AudioPreset::Custom(eq)]),
+    },
+    PresetEntry {
+        key: "8d",
+        label: "🌀 8D Surround",
+        stages: &[PresetStage::Spatial],
+        cost: 1,
+        boost: false,
+        build: |s| {
+            Some(vec![AudioPreset::Surround8D(
+                s.surround_8d.unwrap_or_default(),
+            )])
+        },
+    },
+    PresetEntry {
+        key: "cleanlight",
+        label: "🧹 Чистка · мягко",
+        stages: &[PresetStage::Cleanup],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Cleanup(CleanupStrength::Light)]),
+    },
+    PresetEntry {
+        key: "cleanmedium",
+        label: "🧹 Чистка · средне",
+        stages: &[PresetStage::Cleanup],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Cleanup(CleanupStrength::Medium)]),
+    },
+    PresetEntry {
+        key: "cleanstrong",
+        label: "🧹 Чистка · сильно",
+        stages: &[PresetStage::Cleanup],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Cleanup(CleanupStrength::Strong)]),
+    },
+    PresetEntry {
+        key: "karaoke",
+        label: "🎤 Караоке",
+        stages: &[PresetStage::Vocal],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Vocals(VocalMode::Instrumental)]),
+    },
+    PresetEntry {
+        key: "vocals",
+        label: "🗣 Только вокал",
+        stages: &[PresetStage::Vocal],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Vocals(VocalMode::VocalsOnly)]),
+    },
+    PresetEntry {
+        key: "slowed",
+        label: "🐢 Slowed",
+        stages: &[PresetStage::Tempo],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Tempo(TempoParams::SLOWED)]),
+    },
+    PresetEntry {
+        key: "slowrev",
+        label: "🌙 Slowed + Reverb",
+        stages: &[PresetStage::Tempo, PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |_| {
+            Some(vec![
+                AudioPreset::Tempo(TempoParams::SLOWED),
+                AudioPreset::Reverb(SLOWED_REVERB),
+            ])
+        },
+    },
+    PresetEntry {
+        key: "spedup",
+        label: "⏩ Sped Up",
+        stages: &[PresetStage::Tempo],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Tempo(TempoParams::SPED_UP)]),
+    },
+    PresetEntry {
+        key: "nightcore",
+        label: "🎀 Nightcore",
+        stages: &[PresetStage::Tempo],
+        cost: 1,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Tempo(TempoParams::NIGHTCORE)]),
+    },
+    PresetEntry {
+        key: "reverb",
+        label: "🏛 Reverb",
+        stages: &[PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |s| Some(vec![room_preset(s, Room::Plate, 0.35)]),
+    },
+    PresetEntry {
+        key: "hall",
+        label: "🎻 Зал",
+        stages: &[PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |s| Some(vec![room_preset(s, Room::Hall, 0.3)]),
+    },
+    PresetEntry {
+        key: "club",
+        label: "🪩 Клуб",
+        stages: &[PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |s| Some(vec![room_preset(s, Room::Club, 0.25)]),
+    },
+    PresetEntry {
+        key: "cathedral",
+        label: "⛪ Собор",
+        stages: &[PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |s| Some(vec![room_preset(s, Room::Cathedral, 0.4)]),
+    },
+    PresetEntry {
+        key: "room",
+        label: "🚪 Комната",
+        stages: &[PresetStage::Reverb],
+        cost: 1,
+        boost: false,
+        build: |s| Some(vec![room_preset(s, Room::Small, 0.2)]),
+    },
+    PresetEntry {
+        key: "limiter",
+        label: "🧱 Limiter",
+        stages: &[PresetStage::Dynamics],
+        cost: 0,
+        boost: false,
+        build: |_| Some(vec![AudioPreset::Limiter]),
+    },
+    PresetEntry {
+        key: "hwlight",
+        label: "🛣 Трасса · мягко",
+        stages: &[PresetStage::Dynamics],
+        cost: 1,
+        boost: true,
+        build: |_| Some(vec![AudioPreset::Highway(HighwayStrength::Light)]),
+    },
+    PresetEntry {
+        key: "hwmedium",
+        label: "🛣 Трасса · средне",
+        stages: &[PresetStage::Dynamics],
+        cost: 1,
+        boost: true,
+        build: |_| Some(vec![AudioPreset::Highway(HighwayStrength::Medium)]),
+    },
+    PresetEntry {
+        key: "hwstrong",
+        label: "🛣 Трасса · жёстко",
+        stages: &[PresetStage::Dynamics],
+        cost: 1,
+        boost: true,
+        build: |_| Some(vec![AudioPreset::Highway(HighwayStrength::Strong)]),
+    },
+];
+
+pub fn find(key: &str) -> Option<&'static PresetEntry> {
+    CATALOG.iter().find(|entry| entry.key == key)
+}
+
+// Вкл/выкл пресет. Внутри одного этапа выбор взаимоисключающий:
+// включение нового пресета снимает всех, кто занимает те же этапы.
+pub fn toggle(selected: &mut Vec<String>, key: &str) {
+    let Some(entry) = find(key) else {
+        return;
+    };
+
+    if let Some(pos) = selected.iter().position(|k| k == key) {
+        selected.remove(pos);
+        return;
+    }
+
+    selected.retain(|k| {
+        find(k).is_none_or(|other| !other.stages.iter().any(|s| entry.stages.contains(s)))
+    });
+    selected.push(key.to_string());
+}
+
+// Цена комбинации: сумма цен из каталога, но не меньше одного кредита
+pub fn price(selected: &[String]) -> i32 {
+    selected
+        .iter()
+        .filter_map(|k| find(k))
+        .map(|entry| entry.cost)
+        .sum::<i32>()
+        .max(1)
+}
+
+// Собирает пресеты задания в порядке обработки
+pub fn resolve(selected: &[String], settings: &UserSettings) -> Vec<AudioPreset> {
+    let mut presets: Vec<AudioPreset> = selected
+        .iter()
+        .filter_map(|k| find(k))
+        .filter_map(|entry| (entry.build)(settings))
+        .flatten()
+        .collect();
+    presets.sort_by_key(AudioPreset::stage);
+    presets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::audio_service::SurroundParams;
+    use crate::domain::eq::CustomEq;
+
+    #[test]
+    fn catalog_stage_matches_built_preset() {
+        let settings = UserSettings {
+            custom_eq: Some("60:3".parse::<CustomEq>().unwrap()),
+            ..Default::default()
+        };
+        for entry in CATALOG {
+            let stages: Vec<PresetStage> = (entry.build)(&settings)
+                .unwrap()
+                .iter()
+                .map(AudioPreset::stage)
+                .collect();
+            assert_eq!(stages, entry.stages, "{}", entry.key);
+        }
+    }
+
+    #[test]
+    fn toggle_replaces_preset_of_the_same_stage() {
+        let mut selected = Vec::new();
+        toggle(&mut selected, "bass");
+        toggle(&mut selected, "8d");
+        toggle(&mut selected, "hifi");
+        assert_eq!(selected, ["8d", "hifi"]);
+
+        toggle(&mut selected, "8d");
+        assert_eq!(selected, ["hifi"]);
+
+        // Комбо снимает и темп, и реверб
+        toggle(&mut selected, "slowed");
+        toggle(&mut selected, "reverb");
+        toggle(&mut selected, "slowrev");
+        assert_eq!(selected, ["hifi", "slowrev"]);
+
+        // Комнаты — один этап реверба
+        toggle(&mut selected, "hall");
+        toggle(&mut selected, "club");
+        assert_eq!(selected, ["hifi", "club"]);
+
+        // "Трасса" сама держит пики, поэтому заменяет лимитер
+        toggle(&mut selected, "limiter");
+        toggle(&mut selected, "hwmedium");
+        assert_eq!(selected, ["hifi", "club", "hwmedium"]);
+    }
+
+    #[test]
+    fn resolves_in_processing_order_and_prices_combination() {
+        let selected: Vec<String> = ["limiter", "8d", "slowed", "karaoke", "bass"]
+            .map(String::from)
+            .to_vec();
+        let stages: Vec<PresetStage> = resolve(&selected, &UserSettings::default())
+            .iter()
+            .map(AudioPreset::stage)
+            .collect();
+
+        assert_eq!(
+            stages,
+            [
+                PresetStage::Vocal,
+                PresetStage::Tempo,
+                PresetStage::Tone,
+                PresetStage::Spatial,
+                PresetStage::Dynamics
+            ]
+        );
+        assert_eq!(price(&selected), 4);
+        assert_eq!(price(&["limiter".to_string()]), 1);
+    }
+
+    #[test]
+    fn presets_use_user_parameters() {
+        let params: SurroundParams = "0.3 40 50%".parse().unwrap();
+        assert_eq!(params.to_string(), "0.3 40 50%");
+        let settings = UserSettings {
+            surround_8d: Some(params),
+            ..Default::default()
+        };
+        let presets = resolve(&["8d".to_string()], &settings);
+        assert!(matches!(presets[..], [AudioPreset::Surround8D(p)] if p == params));
+        assert!("2 20".parse::<SurroundParams>().is_err());
+
+        // Доля реверба от пользователя — во всех комнатах, без неё — из каталога
+        let wet = UserSettings {
+            reverb_mix: Some(0.6),
+            ..Default::default()
+        };
+        for (settings, mix) in [(&wet, 0.6), (&UserSettings::default(), 0.4)] {
+            let presets = resolve(&["cathedral".to_string()], settings);
+            assert!(matches!(presets[..], [AudioPreset::Reverb(p)] if p.mix == mix));
+        }
+    }
+}
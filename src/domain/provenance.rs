@@ -0,0 +1,167 @@
+use crate::domain::audio_service::{AudioPreset, LoudnessTarget};
+use crate::domain::preset_catalog::{self, PresetEntry};
+
+pub const BOT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Описания TXXX-фреймов (в FLAC/Opus/M4A — имена тегов)
+pub const TAG_PRESETS: &str = "CARBOT_PRESETS";
+pub const TAG_LOUDNESS: &str = "CARBOT_LOUDNESS";
+pub const TAG_VERSION: &str = "CARBOT_VERSION";
+pub const TAG_JOB: &str = "CARBOT_JOB";
+
+// Чем и как бот уже обработал файл
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    // Ключи пресетов из каталога
+    pub presets: Vec<String>,
+    pub loudness: LoudnessTarget,
+    pub bot_version: String,
+    pub job_id: String,
+}
+
+impl Provenance {
+    pub fn new(job_id: &str, preset_keys: &[String], presets: &[AudioPreset]) -> Self {
+        Self {
+            presets: preset_keys.to_vec(),
+            loudness: presets
+                .iter()
+                .find_map(AudioPreset::loudness)
+                .unwrap_or_default(),
+            bot_version: BOT_VERSION.to_string(),
+            job_id: job_id.to_string(),
+        }
+    }
+
+    // Пары (описание, значение) для записи в теги
+    pub fn tags(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (TAG_PRESETS, self.presets.join(",")),
+            (
+                TAG_LOUDNESS,
+                format!(
+                    "{} LUFS {} dBTP",
+                    self.loudness.lufs, self.loudness.true_peak
+                ),
+            ),
+            (TAG_VERSION, self.bot_version.clone()),
+            (TAG_JOB, self.job_id.clone()),
+        ]
+    }
+
+    // Обратно из тегов файла. Регистр имён не важен: форматы хранят его по-разному.
+    // Без списка пресетов файл считаем необработанным
+    pub fn from_tags<'a>(tags: impl IntoIterator<Item = (&'a str, &'a str)>) -> Option<Self> {
+        let mut presets = None;
+        let mut loudness = LoudnessTarget::default();
+        let mut bot_version = String::new();
+        let mut job_id = String::new();
+
+        for (key, value) in tags {
+            let value = value.trim();
+            if key.eq_ignore_ascii_case(TAG_PRESETS) {
+                presets = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|k| !k.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            } else if key.eq_ignore_ascii_case(TAG_LOUDNESS) {
+                let numbers: Vec<f32> = value
+                    .split_whitespace()
+                    .filter_map(|token| token.parse().ok())
+                    .collect();
+                if let [lufs, true_peak] = numbers.as_slice() {
+                    loudness = LoudnessTarget {
+                        lufs: *lufs,
+                        true_peak: *true_peak,
+                    };
+                }
+            } else if key.eq_ignore_ascii_case(TAG_VERSION) {
+                bot_version = value.to_string();
+            } else if key.eq_ignore_ascii_case(TAG_JOB) {
+                job_id = value.to_string();
+            }
+        }
+
+        Some(Self {
+            presets: presets?,
+            loudness,
+            bot_version,
+            job_id,
+        })
+    }
+
+    // Подписи уже применённых пресетов для сообщения пользователю
+    pub fn labels(&self) -> Vec<&'static str> {
+        self.presets
+            .iter()
+            .filter_map(|k| preset_catalog::find(k))
+            .map(|entry| entry.label)
+            .collect()
+    }
+
+    // Буст-пресеты из нового задания, которые ложатся на тот же этап,
+    // где файл уже получил буст
+    pub fn repeated_boosts(&self, requested: &[String]) -> Vec<&'static PresetEntry> {
+        let applied: Vec<&PresetEntry> = boosts(&self.presets).collect();
+        boosts(requested)
+            .filter(|entry| {
+                applied
+                    .iter()
+                    .any(|done| done.stages.iter().any(|s| entry.stages.contains(s)))
+            })
+            .collect()
+    }
+}
+
+fn boosts(keys: &[String]) -> impl Iterator<Item = &'static PresetEntry> + '_ {
+    keys.iter()
+        .filter_map(|k| preset_catalog::find(k))
+        .filter(|entry| entry.boost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(list: &[&str]) -> Vec<String> {
+        list.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn round_trips_through_tags() {
+        let provenance = Provenance::new(
+            "a1b2c3",
+            &keys(&["extreme", "8d"]),
+            &[AudioPreset::ExtremeLow],
+        );
+        let tags = provenance.tags();
+        // Часть форматов меняет регистр имён тегов
+        let lower: Vec<(String, String)> = tags
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), v.clone()))
+            .collect();
+        let parsed = Provenance::from_tags(lower.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        assert_eq!(parsed, Some(provenance));
+        assert_eq!(Provenance::from_tags([("title", "Лесник")]), None);
+    }
+
+    #[test]
+    fn detects_stacked_boosts() {
+        let previous = Provenance::new("job", &keys(&["bass", "8d", "hwlight"]), &[]);
+        let repeated: Vec<&str> = previous
+            .repeated_boosts(&keys(&["extreme", "8d", "limiter"]))
+            .iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(repeated, ["extreme"]);
+        assert!(
+            previous
+                .repeated_boosts(&keys(&["hifi", "hall"]))
+                .is_empty()
+        );
+        assert_eq!(previous.repeated_boosts(&keys(&["hwstrong"])).len(), 1);
+    }
+}
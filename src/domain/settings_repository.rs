@@ -0,0 +1,37 @@
+use crate::domain::audio_service::{OutputMode, SurroundParams};
+use crate::domain::calibration::CabinProfile;
+use crate::domain::driver_seat::DriverSeat;
+use crate::domain::eq::CustomEq;
+use crate::domain::head_unit::HeadUnitMode;
+use crate::domain::output_format::OutputFormat;
+use crate::domain::track_edges::{Fades, SilenceTrim};
+use async_trait::async_trait;
+
+// Персональные настройки обработки пользователя
+#[derive(Debug, Clone, Default)]
+pub struct UserSettings {
+    pub custom_eq: Option<CustomEq>,
+    pub cabin_profile: Option<CabinProfile>,
+    // /calibrate прислал тестовый сигнал и ждёт голосовое с записью салона
+    pub awaiting_calibration: bool,
+    pub driver_seat: Option<DriverSeat>,
+    // Своё вращение для пресета «8D» (/8d); None — по умолчанию
+    pub surround_8d: Option<SurroundParams>,
+    // Доля «мокрого» сигнала для пресетов комнат (/reverb), 0.0..=1.0
+    pub reverb_mix: Option<f32>,
+    // Диаметр штатных динамиков, см (для пресета «Штатка»)
+    pub speaker_size_cm: Option<f32>,
+    pub output_mode: OutputMode,
+    pub output_format: OutputFormat,
+    pub silence_trim: Option<SilenceTrim>,
+    pub fades: Option<Fades>,
+    pub head_unit: Option<HeadUnitMode>,
+}
+
+#[async_trait]
+pub trait SettingsRepository: Send + Sync {
+    async fn get_settings(&self, user_id: i64) -> UserSettings;
+
+    async fn save_settings(&self, user_id: i64, settings: &UserSettings)
+    -> Result<(), sqlx::Error>;
+}
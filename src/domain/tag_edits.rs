@@ -0,0 +1,86 @@
+use thiserror::Error;
+
+// Длиннее ID3 хранит, но ни одна магнитола не покажет
+const MAX_TAG_CHARS: usize = 200;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TagEditError {
+    #[error(
+        "Не понял строку «{0}». Пиши по строке на поле: «Название: …», «Исполнитель: …», «Альбом: …»"
+    )]
+    UnknownLine(String),
+
+    #[error("Слишком длинное значение — максимум {MAX_TAG_CHARS} символов")]
+    TooLong,
+}
+
+// Правки тегов от пользователя перед обработкой; None — берём из метаданных ролика
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagEdits {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    // file_id фото из Telegram, скачиваем только при запуске
+    pub cover_file_id: Option<String>,
+}
+
+impl TagEdits {
+    pub fn is_empty(&self) -> bool {
+        *self == TagEdits::default()
+    }
+
+    // Разбирает сообщение вида "Название: Кукла колдуна\nИсполнитель: КиШ".
+    // Пустое значение ("Альбом:") сбрасывает правку. Правки применяются, только если
+    // понятны все строки — чтобы опечатка не записалась наполовину.
+    pub fn apply_text(&mut self, text: &str) -> Result<(), TagEditError> {
+        let mut edited = self.clone();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| TagEditError::UnknownLine(line.to_string()))?;
+            let value = value.trim();
+            if value.chars().count() > MAX_TAG_CHARS {
+                return Err(TagEditError::TooLong);
+            }
+            let value = (!value.is_empty()).then(|| value.to_string());
+
+            match key.trim().to_lowercase().as_str() {
+                "название" | "трек" | "title" => edited.title = value,
+                "исполнитель" | "артист" | "artist" => edited.artist = value,
+                "альбом" | "album" => edited.album = value,
+                _ => return Err(TagEditError::UnknownLine(line.to_string())),
+            }
+        }
+        *self = edited;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_tag_lines() {
+        let mut edits = TagEdits {
+            album: Some("Старый".into()),
+            ..Default::default()
+        };
+        edits
+            .apply_text("Название: Кукла колдуна\nARTIST: Король и Шут\nАльбом:")
+            .unwrap();
+        assert_eq!(edits.title.as_deref(), Some("Кукла колдуна"));
+        assert_eq!(edits.artist.as_deref(), Some("Король и Шут"));
+        assert_eq!(edits.album, None);
+    }
+
+    #[test]
+    fn rejects_unknown_lines_without_partial_update() {
+        let mut edits = TagEdits::default();
+        assert!(matches!(
+            edits.apply_text("Название: Лесник\nГод: 1996"),
+            Err(TagEditError::UnknownLine(_))
+        ));
+        assert!(edits.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use regex::{Regex, RegexBuilder};
+use thiserror::Error;
+
+// Правила по умолчанию вшиты в бинарник, TITLE_RULES_PATH их заменяет
+pub const DEFAULT_TITLE_RULES: &str = include_str!("../../config/title_rules.txt");
+
+// Разделители, которые остаются висеть после вырезания: "Песня | " -> "Песня"
+const DANGLING: [char; 6] = ['-', '–', '—', '|', ':', ','];
+
+#[derive(Error, Debug)]
+pub enum TitleRulesError {
+    #[error("Правило в строке {line} не разбирается: {source}")]
+    InvalidRule { line: usize, source: regex::Error },
+}
+
+// Набор регулярных выражений, чьи совпадения вырезаются из названия ролика
+#[derive(Debug, Clone)]
+pub struct TitleRules {
+    rules: Vec<Regex>,
+}
+
+impl Default for TitleRules {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TITLE_RULES).expect("встроенные правила должны разбираться")
+    }
+}
+
+impl TitleRules {
+    // По выражению на строку; пустые строки и строки с "# " — комментарии
+    // (сам "#" в правиле нужен для хэштегов)
+    pub fn parse(text: &str) -> Result<Self, TitleRulesError> {
+        let mut rules = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("# ") || line == "#" {
+                continue;
+            }
+            let rule = RegexBuilder::new(line)
+                .case_insensitive(true)
+                .build()
+                .map_err(|source| TitleRulesError::InvalidRule {
+                    line: index + 1,
+                    source,
+                })?;
+            rules.push(rule);
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn clean(&self, title: &str) -> String {
+        let mut cleaned = title.to_string();
+        for rule in &self.rules {
+            cleaned = rule.replace_all(&cleaned, " ").into_owned();
+        }
+        let cleaned = tidy(&cleaned);
+        // Если правила съели всё, лучше мусорное название, чем пустое
+        if cleaned.is_empty() {
+            title.trim().to_string()
+        } else {
+            cleaned
+        }
+    }
+}
+
+// Пустые скобки, двойные пробелы и висящие по краям разделители
+fn tidy(title: &str) -> String {
+    let mut title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    loop {
+        let before = title.len();
+        title = title
+            .replace("()", "")
+            .replace("[]", "")
+            .replace("( )", "")
+            .replace("[ ]", "");
+        title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        title = title
+            .trim_matches(|c: char| c.is_whitespace() || DANGLING.contains(&c))
+            .to_string();
+        if title.len() == before {
+            return title;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_title_corpus() {
+        let rules = TitleRules::default();
+        let corpus = [
+            (
+                "Кино - Группа крови (official video)",
+                "Кино - Группа крови",
+            ),
+            ("Adele - Hello [Official Music Video]", "Adele - Hello"),
+            (
+                "Imagine Dragons - Believer (Lyrics)",
+                "Imagine Dragons - Believer",
+            ),
+            ("Баста - Сансара | Премьера клипа", "Баста - Сансара"),
+            (
+                "Король и Шут - Лесник (Официальное видео) [HQ]",
+                "Король и Шут - Лесник",
+            ),
+            ("4Kings - Night Drive 4K", "4Kings - Night Drive"),
+            (
+                "Макс Корж - Малиновый закат (Премьера клипа, 2024) #shorts",
+                "Макс Корж - Малиновый закат",
+            ),
+            (
+                "Daft Punk - Get Lucky (feat. Pharrell Williams)",
+                "Daft Punk - Get Lucky (feat. Pharrell Williams)",
+            ),
+        ];
+        for (title, expected) in corpus {
+            assert_eq!(rules.clean(title), expected, "{title}");
+        }
+    }
+
+    #[test]
+    fn reports_broken_rule_line() {
+        let err = TitleRules::parse("# комментарий\n\\bok\\b\n(unclosed").unwrap_err();
+        assert!(matches!(err, TitleRulesError::InvalidRule { line: 3, .. }));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Паузы короче этого не считаем тишиной на краях (дыхание перед первой нотой)
+pub const MIN_SILENCE_S: f32 = 0.5;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EdgesError {
+    #[error("Не понял «{0}» — нужно число")]
+    InvalidNumber(String),
+
+    #[error("Порог тишины {0} дБ вне диапазона -80…-20")]
+    ThresholdOutOfRange(f32),
+
+    #[error("Длина фейда {0} с вне диапазона 0–15")]
+    FadeOutOfRange(f32),
+
+    #[error("Нужно одно или два числа: фейд в начале и в конце, в секундах")]
+    WrongFadeCount,
+}
+
+fn parse_number(token: &str) -> Result<f32, EdgesError> {
+    token
+        .trim_end_matches("dB")
+        .trim_end_matches('s')
+        .parse()
+        .map_err(|_| EdgesError::InvalidNumber(token.to_string()))
+}
+
+// Обрезка тишины по краям: всё тише порога считается тишиной
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceTrim {
+    pub threshold_db: f32,
+}
+
+impl Default for SilenceTrim {
+    fn default() -> Self {
+        Self {
+            threshold_db: -50.0,
+        }
+    }
+}
+
+impl SilenceTrim {
+    pub fn new(threshold_db: f32) -> Result<Self, EdgesError> {
+        if !(-80.0..=-20.0).contains(&threshold_db) {
+            return Err(EdgesError::ThresholdOutOfRange(threshold_db));
+        }
+        Ok(Self { threshold_db })
+    }
+}
+
+// Тот же формат, что после /trim: порог в дБ
+impl fmt::Display for SilenceTrim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.threshold_db)
+    }
+}
+
+impl FromStr for SilenceTrim {
+    type Err = EdgesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Ok(Self::default()),
+            token => SilenceTrim::new(parse_number(token)?),
+        }
+    }
+}
+
+// Плавное появление и затухание, секунды
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fades {
+    pub fade_in_s: f32,
+    pub fade_out_s: f32,
+}
+
+impl Fades {
+    pub fn new(fade_in_s: f32, fade_out_s: f32) -> Result<Self, EdgesError> {
+        for length in [fade_in_s, fade_out_s] {
+            if !(0.0..=15.0).contains(&length) {
+                return Err(EdgesError::FadeOutOfRange(length));
+            }
+        }
+        Ok(Self {
+            fade_in_s,
+            fade_out_s,
+        })
+    }
+}
+
+// Тот же формат, что после /fade: "в_начале в_конце"
+impl fmt::Display for Fades {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.fade_in_s, self.fade_out_s)
+    }
+}
+
+impl FromStr for Fades {
+    type Err = EdgesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split_whitespace()
+            .map(parse_number)
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        match numbers.as_slice() {
+            [both] => Fades::new(*both, *both),
+            [fade_in, fade_out] => Fades::new(*fade_in, *fade_out),
+            _ => Err(EdgesError::WrongFadeCount),
+        }
+    }
+}
+
+// Границы звучащей части (начало, конец в секундах) по логу silencedetect.
+// Тишина считается краевой, только если касается начала или конца трека.
+pub fn sound_bounds(silencedetect_log: &str, duration: f32) -> (f32, f32) {
+    let value_after = |line: &str, key: &str| -> Option<f32> {
+        let rest = &line[line.find(key)? + key.len()..];
+        rest.split_whitespace().next()?.parse().ok()
+    };
+
+    // Пары (начало, конец) тишины; у последней конца может не быть — тишина до EOF
+    let mut silences: Vec<(f32, Option<f32>)> = Vec::new();
+    for line in silencedetect_log.lines() {
+        if let Some(start) = value_after(line, "silence_start:") {
+            silences.push((start, None));
+        } else if let Some(end) = value_after(line, "silence_end:")
+            && let Some(last) = silences.last_mut()
+        {
+            last.1 = Some(end);
+        }
+    }
+
+    let mut start = 0.0f32;
+    let mut end = duration;
+    if let Some((silence_start, Some(silence_end))) = silences.first()
+        && *silence_start <= 0.05
+    {
+        start = *silence_end;
+    }
+    if let Some((silence_start, silence_end)) = silences.last()
+        && silence_end.is_none_or(|e| e >= duration - 0.05)
+        && *silence_start > start
+    {
+        end = *silence_start;
+    }
+
+    if end - start < MIN_SILENCE_S {
+        // Весь трек "тишина" (например, слишком высокий порог) — ничего не режем
+        return (0.0, duration);
+    }
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_sound_between_edge_silences() {
+        let log = "\
+[silencedetect @ 0x1] silence_start: 0
+[silencedetect @ 0x1] silence_end: 12.5 | silence_duration: 12.5
+[silencedetect @ 0x1] silence_start: 95.2
+[silencedetect @ 0x1] silence_end: 96.1 | silence_duration: 0.9
+[silencedetect @ 0x1] silence_start: 180.4";
+        assert_eq!(sound_bounds(log, 200.0), (12.5, 180.4));
+        assert_eq!(sound_bounds("", 200.0), (0.0, 200.0));
+    }
+
+    #[test]
+    fn parses_settings() {
+        assert_eq!("2".parse::<Fades>(), Ok(Fades::new(2.0, 2.0).unwrap()));
+        assert_eq!("1 4s".parse::<Fades>(), Ok(Fades::new(1.0, 4.0).unwrap()));
+        assert_eq!("".parse::<SilenceTrim>(), Ok(SilenceTrim::default()));
+        assert_eq!(
+            "-10".parse::<SilenceTrim>(),
+            Err(EdgesError::ThresholdOutOfRange(-10.0))
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// Разделители "Исполнитель - Название", от самых однозначных
+const SEPARATORS: [&str; 5] = [" - ", " – ", " — ", " -- ", " | "];
+
+// Как на YouTube помечают приглашённых исполнителей
+const FEAT_MARKERS: [&str; 5] = ["feat.", "feat ", "ft.", "ft ", "featuring "];
+
+// Что знаем о ролике: название, канал и поля artist/track, которые yt-dlp
+// заполняет для музыкальных релизов
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TrackSource<'a> {
+    pub title: &'a str,
+    pub channel: Option<&'a str>,
+    pub artist: Option<&'a str>,
+    pub track: Option<&'a str>,
+}
+
+// Исполнитель и название для тегов
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackName {
+    pub artist: String,
+    pub title: String,
+    pub featured: Vec<String>,
+}
+
+impl TrackName {
+    // Название для тега: приглашённые идут в скобках, а исполнитель остаётся
+    // один — иначе магнитола разносит альбомы по разным папкам
+    pub fn tag_title(&self) -> String {
+        if self.featured.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} (feat. {})", self.title, join_artists(&self.featured))
+        }
+    }
+}
+
+pub fn parse_track_name(source: TrackSource) -> TrackName {
+    let channel = source.channel.map(clean_channel).unwrap_or_default();
+
+    // Поля yt-dlp берутся из описания релиза — им верим больше, чем названию
+    if let (Some(artist), Some(track)) = (non_empty(source.artist), non_empty(source.track)) {
+        // Несколько исполнителей yt-dlp перечисляет через запятую
+        let mut artists: Vec<String> = artist
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        // В поле могут оказаться одни запятые — тогда разбираем название
+        if !artists.is_empty() {
+            let main = artists.remove(0);
+            let (title, mut featured) = split_feat(track);
+            featured.retain(|f| !artists.contains(f));
+            artists.extend(featured);
+            return TrackName {
+                artist: main,
+                title,
+                featured: artists,
+            };
+        }
+    }
+
+    let (artist, title) = match split_artist_title(source.title) {
+        // "Название - Канал" встречается реже, но встречается
+        Some((left, right)) if same_name(right, &channel) && !same_name(left, &channel) => {
+            (right.to_string(), left.to_string())
+        }
+        Some((left, right)) => (left.to_string(), right.to_string()),
+        None => (channel.clone(), source.title.trim().to_string()),
+    };
+
+    let (artist, mut featured) = split_feat(&artist);
+    let (title, title_featured) = split_feat(&title);
+    featured.extend(title_featured);
+
+    let artist = if artist.is_empty() {
+        "Unknown Artist".to_string()
+    } else {
+        artist
+    };
+    TrackName {
+        artist,
+        title: if title.is_empty() {
+            source.title.trim().to_string()
+        } else {
+            title
+        },
+        featured,
+    }
+}
+
+// Автоканалы YouTube Music ("Кино - Topic") и VEVO ("AdeleVEVO")
+pub fn clean_channel(channel: &str) -> String {
+    let channel = channel.trim();
+    let channel = channel
+        .strip_suffix(" - Topic")
+        .or_else(|| channel.strip_suffix("VEVO"))
+        .or_else(|| channel.strip_suffix("Vevo"))
+        .unwrap_or(channel);
+    channel.trim().to_string()
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    !b.is_empty() && a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+fn split_artist_title(title: &str) -> Option<(&str, &str)> {
+    let title = title.trim();
+    for sep in SEPARATORS {
+        if let Some((artist, name)) = title.split_once(sep) {
+            let (artist, name) = (artist.trim(), name.trim());
+            if !artist.is_empty() && !name.is_empty() {
+                return Some((artist, name));
+            }
+        }
+    }
+
+    // Исполнитель «Название» и Исполнитель "Название"
+    for (open, close) in [('«', '»'), ('"', '"'), ('“', '”')] {
+        if let Some((artist, rest)) = title.split_once(open)
+            && let Some((name, _)) = rest.split_once(close)
+        {
+            let (artist, name) = (artist.trim(), name.trim());
+            if !artist.is_empty() && !name.is_empty() {
+                return Some((artist, name));
+            }
+        }
+    }
+    None
+}
+
+// Отделяет "feat. X" — в скобках или в конце строки
+fn split_feat(text: &str) -> (String, Vec<String>) {
+    // ASCII-регистр не меняет длину в байтах — индексы совпадают с text
+    let lower = text.to_ascii_lowercase();
+    let found = FEAT_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            lower.match_indices(marker).find(|(i, _)| {
+                // Только отдельным словом: "Left Feat" не трогаем
+                *i == 0 || matches!(lower[..*i].chars().last(), Some(' ' | '(' | '['))
+            })
+        })
+        .min_by_key(|(i, _)| *i);
+
+    let Some((start, marker)) = found else {
+        return (text.trim().to_string(), Vec::new());
+    };
+
+    let before = &text[..start];
+    let after = &text[start + marker.len()..];
+    let bracket = before
+        .trim_end()
+        .chars()
+        .last()
+        .filter(|c| *c == '(' || *c == '[');
+    let (names, rest) = match bracket {
+        Some(open) => {
+            let close = if open == '(' { ')' } else { ']' };
+            let (names, rest) = after.split_once(close).unwrap_or((after, ""));
+            let before = before.trim_end();
+            (names, format!("{} {}", &before[..before.len() - 1], rest))
+        }
+        None => (after, before.to_string()),
+    };
+
+    let rest = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+    (rest, split_artists(names))
+}
+
+fn split_artists(names: &str) -> Vec<String> {
+    names
+        .split([',', '&'])
+        .flat_map(|part| part.split(" and "))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn join_artists(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} & {}", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(title: &str, channel: &str) -> (String, String) {
+        let name = parse_track_name(TrackSource {
+            title,
+            channel: Some(channel),
+            ..Default::default()
+        });
+        (name.artist.clone(), name.tag_title())
+    }
+
+    #[test]
+    fn parses_title_corpus() {
+        let corpus = [
+            (
+                "Кино - Группа крови",
+                "Kino Official",
+                "Кино",
+                "Группа крови",
+            ),
+            ("Король и Шут «Лесник»", "КиШ", "Король и Шут", "Лесник"),
+            (
+                "Daft Punk – Get Lucky (feat. Pharrell Williams)",
+                "Daft Punk",
+                "Daft Punk",
+                "Get Lucky (feat. Pharrell Williams)",
+            ),
+            (
+                "Macklemore ft. Ryan Lewis — Thrift Shop",
+                "Macklemore",
+                "Macklemore",
+                "Thrift Shop (feat. Ryan Lewis)",
+            ),
+            (
+                "Shape of You - Ed Sheeran",
+                "Ed Sheeran",
+                "Ed Sheeran",
+                "Shape of You",
+            ),
+            ("Hello", "AdeleVEVO", "Adele", "Hello"),
+            (
+                "Звезда по имени Солнце",
+                "Кино - Topic",
+                "Кино",
+                "Звезда по имени Солнце",
+            ),
+            (
+                "Artist - Song [feat. A, B & C]",
+                "x",
+                "Artist",
+                "Song (feat. A, B & C)",
+            ),
+        ];
+        for (title, channel, artist, expected) in corpus {
+            assert_eq!(
+                parse(title, channel),
+                (artist.to_string(), expected.to_string()),
+                "{title}"
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_yt_dlp_music_fields() {
+        let name = parse_track_name(TrackSource {
+            title: "BEST SONG EVER 2024 (Official Video)",
+            channel: Some("Random Uploads"),
+            artist: Some("Dua Lipa, DaBaby"),
+            track: Some("Levitating (feat. DaBaby)"),
+        });
+        assert_eq!(name.artist, "Dua Lipa");
+        assert_eq!(name.tag_title(), "Levitating (feat. DaBaby)");
+
+        // Тег TPE1="," у загруженного файла не должен ронять разбор
+        let name = parse_track_name(TrackSource {
+            title: "Кино - Кукушка",
+            channel: None,
+            artist: Some(" , "),
+            track: Some("Кукушка"),
+        });
+        assert_eq!(name.artist, "Кино");
+        assert_eq!(name.tag_title(), "Кукушка");
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::str::FromStr;
+use url::Url;
+
+// Жанры, которые узнаём в тегах и категориях ролика: (как пишут, как записать)
+const KNOWN_GENRES: [(&str, &str); 26] = [
+    ("drum and bass", "Drum & Bass"),
+    ("drum & bass", "Drum & Bass"),
+    ("dnb", "Drum & Bass"),
+    ("hip hop", "Hip-Hop"),
+    ("hip-hop", "Hip-Hop"),
+    ("рэп", "Hip-Hop"),
+    ("rap", "Hip-Hop"),
+    ("r&b", "R&B"),
+    ("lo-fi", "Lo-Fi"),
+    ("lofi", "Lo-Fi"),
+    ("phonk", "Phonk"),
+    ("techno", "Techno"),
+    ("house", "House"),
+    ("trance", "Trance"),
+    ("dubstep", "Dubstep"),
+    ("electronic", "Electronic"),
+    ("metal", "Metal"),
+    ("рок", "Rock"),
+    ("rock", "Rock"),
+    ("поп", "Pop"),
+    ("pop", "Pop"),
+    ("jazz", "Jazz"),
+    ("blues", "Blues"),
+    ("classical", "Classical"),
+    ("reggae", "Reggae"),
+    ("шансон", "Шансон"),
+];
+
+// Место трека в плейлисте или альбоме
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPosition {
+    pub number: u32,
+    pub total: Option<u32>,
+}
+
+// Как в ID3 TRCK: "3" или "3/12"
+impl fmt::Display for TrackPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "{}/{}", self.number, total),
+            None => write!(f, "{}", self.number),
+        }
+    }
+}
+
+// Разбирает TRCK: "3", "3/12"; мусор вроде "A1" с винила не считаем номером
+impl FromStr for TrackPosition {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, total) = match s.trim().split_once('/') {
+            Some((number, total)) => (number, Some(total.trim().parse()?)),
+            None => (s.trim(), None),
+        };
+        Ok(Self {
+            number: number.trim().parse()?,
+            total,
+        })
+    }
+}
+
+// Год релиза, а если его нет — год загрузки ролика (upload_date = "20240131")
+pub fn release_year(release_year: Option<i32>, upload_date: Option<&str>) -> Option<i32> {
+    release_year.filter(|y| *y > 0).or_else(|| {
+        upload_date
+            .and_then(|date| date.get(..4))
+            .and_then(|year| year.parse().ok())
+    })
+}
+
+// Плейлист из ссылки "watch?v=...&list=...". Автоматические миксы YouTube (RD...)
+// бесконечны и у каждого свои — номер трека в них ничего не значит
+pub fn playlist_id(url: &str) -> Option<String> {
+    Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "list")
+        .map(|(_, list)| list.into_owned())
+        .filter(|list| !list.is_empty() && !list.starts_with("RD"))
+}
+
+// Место ролика среди роликов плейлиста, считая с единицы
+pub fn playlist_position(video_id: &str, playlist: &[String]) -> Option<TrackPosition> {
+    let index = playlist.iter().position(|id| id == video_id)?;
+    Some(TrackPosition {
+        number: index as u32 + 1,
+        total: Some(playlist.len() as u32),
+    })
+}
+
+// Жанр: явный из yt-dlp, иначе первый узнанный в тегах, иначе в категориях.
+// Категория "Music" жанром не считается
+pub fn detect_genre(genre: Option<&str>, tags: &[String], categories: &[String]) -> Option<String> {
+    if let Some(genre) = genre.map(str::trim).filter(|g| !g.is_empty()) {
+        return Some(genre.to_string());
+    }
+    tags.iter()
+        .chain(categories)
+        .find_map(|tag| known_genre(tag))
+        .map(str::to_string)
+}
+
+fn known_genre(tag: &str) -> Option<&'static str> {
+    let tag = tag.trim().to_lowercase();
+    KNOWN_GENRES
+        .iter()
+        .find(|(alias, _)| {
+            // Целым словом: "popcorn" — не поп
+            tag.match_indices(alias).any(|(i, _)| {
+                let before = tag[..i].chars().last();
+                let after = tag[i + alias.len()..].chars().next();
+                !before.is_some_and(char::is_alphanumeric)
+                    && !after.is_some_and(char::is_alphanumeric)
+            })
+        })
+        .map(|(_, genre)| *genre)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_genre_from_tags() {
+        let tags = |list: &[&str]| list.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            detect_genre(
+                None,
+                &tags(&["popcorn", "русский рэп 2024"]),
+                &tags(&["Music"])
+            ),
+            Some("Hip-Hop".into())
+        );
+        assert_eq!(
+            detect_genre(Some("Synthwave"), &tags(&["rock"]), &[]),
+            Some("Synthwave".into())
+        );
+        assert_eq!(
+            detect_genre(None, &tags(&["vlog"]), &tags(&["Music"])),
+            None
+        );
+    }
+
+    #[test]
+    fn year_falls_back_to_upload_date() {
+        assert_eq!(release_year(Some(1988), Some("20190101")), Some(1988));
+        assert_eq!(release_year(None, Some("20240131")), Some(2024));
+        assert_eq!(release_year(None, None), None);
+        assert_eq!(
+            TrackPosition {
+                number: 3,
+                total: Some(12)
+            }
+            .to_string(),
+            "3/12"
+        );
+        assert_eq!(
+            "7".parse(),
+            Ok(TrackPosition {
+                number: 7,
+                total: None
+            })
+        );
+        assert!("A1".parse::<TrackPosition>().is_err());
+    }
+
+    #[test]
+    fn finds_position_in_linked_playlist() {
+        let url = "https://www.youtube.com/watch?v=bbb&list=OLAK5uy_album&index=2";
+        assert_eq!(playlist_id(url), Some("OLAK5uy_album".into()));
+        assert_eq!(playlist_id("https://youtu.be/bbb?list=RDbbb"), None);
+        assert_eq!(playlist_id("https://youtu.be/bbb"), None);
+
+        let playlist = ["aaa", "bbb", "ccc"].map(String::from);
+        assert_eq!(
+            playlist_position("bbb", &playlist).map(|p| p.to_string()),
+            Some("2/3".into())
+        );
+        assert_eq!(playlist_position("zzz", &playlist), None);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::domain::audio_service::AudioError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+
+// Нужная нам часть `ffprobe -of json`: длительность и теги
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ProbeOutput {
+    format: ProbeSection,
+    streams: Vec<ProbeSection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ProbeSection {
+    // ffprobe отдаёт числа строками
+    duration: Option<String>,
+    tags: HashMap<String, String>,
+}
+
+// Что знаем о присланном файле
+#[derive(Debug, Default)]
+pub struct AudioProbe {
+    pub duration: Option<f64>,
+    // Теги контейнера и потоков вместе: в Ogg они живут у потока.
+    // TXXX из ID3 приходят под своим описанием
+    pub tags: HashMap<String, String>,
+}
+
+impl AudioProbe {
+    pub fn duration_secs(&self) -> u64 {
+        self.duration.unwrap_or(0.0).max(0.0).round() as u64
+    }
+
+    // Регистр имён у форматов разный: "title" в ID3, "TITLE" во Vorbis comments
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.trim())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+pub async fn probe_audio(path: &Path) -> Result<AudioProbe, AudioError> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration:format_tags:stream_tags",
+            "-of",
+            "json",
+        ])
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(AudioError::ProcessingError(
+            "Не удалось прочитать файл — он точно аудио?".into(),
+        ));
+    }
+
+    let parsed: ProbeOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AudioError::ProcessingError(format!("Непонятный ответ ffprobe: {}", e)))?;
+
+    let duration = parsed
+        .format
+        .duration
+        .as_deref()
+        .and_then(|d| d.parse().ok());
+    // Теги контейнера важнее тегов потока
+    let mut tags = HashMap::new();
+    for section in parsed.streams.into_iter().chain([parsed.format]) {
+        tags.extend(section.tags);
+    }
+    Ok(AudioProbe { duration, tags })
+}
@@ -0,0 +1,172 @@
+use crate::domain::audio_service::SurroundParams;
+use crate::infrastructure::hrir::TILT_STEP_DEG;
+use std::path::{Path, PathBuf};
+
+// Виртуальные колонки по кругу: (имя канала в раскладке octagonal, азимут).
+// Азимут в градусах против часовой стрелки, 0 — прямо перед слушателем (как в sofalizer).
+pub const SPEAKERS: [(&str, f32); 8] = [
+    ("FC", 0.0),
+    ("FL", 45.0),
+    ("SL", 90.0),
+    ("BL", 135.0),
+    ("BC", 180.0),
+    ("BR", 225.0),
+    ("SR", 270.0),
+    ("FR", 315.0),
+];
+
+// Чем рендерить кольцо колонок в стерео
+#[derive(Debug, Clone, Copy)]
+pub enum Binaural<'a> {
+    // Измеренный HRTF из SOFA-файла (sofalizer)
+    Sofa(&'a Path),
+    // Свои HRIR сферической головы из каталога (afir на каждую колонку)
+    Hrir(&'a Path),
+    // Обычная панорама громкостью: без фазовых сдвигов, безопасна для моно
+    Pan,
+}
+
+// Наклонённое кольцо: слева источник поднимается, справа опускается
+pub fn speaker_elevation(azimuth: f32, tilt_deg: f32) -> f32 {
+    tilt_deg * azimuth.to_radians().sin()
+}
+
+// HRIR колонки для высоты, округлённой до градуса
+pub fn hrir_path(dir: &Path, name: &str, elevation: f32) -> PathBuf {
+    dir.join(format!(
+        "{}_{:+}.wav",
+        name.to_lowercase(),
+        elevation.round() as i32
+    ))
+}
+
+// Собирает граф 8D: моно-источник "ездит" по кольцу из 8 виртуальных колонок,
+// а кольцо рендерится в бинаурал через HRTF (sofalizer) или свои HRIR.
+// Панорама — для моно-безопасного варианта.
+pub fn surround_filter(params: &SurroundParams, render: Binaural) -> String {
+    let mut graph = String::new();
+
+    // 1. Сводим в моно и размножаем на 8 колонок
+    graph.push_str("pan=mono|c0=0.5*c0+0.5*c1,asplit=8");
+    for i in 0..SPEAKERS.len() {
+        graph.push_str(&format!("[s{}]", i));
+    }
+    graph.push(';');
+
+    // 2. Огибающая громкости каждой колонки следует за вращающимся источником
+    for (i, (_, azimuth)) in SPEAKERS.iter().enumerate() {
+        graph.push_str(&format!(
+            "[s{i}]volume='pow(max(0,cos(2*PI*{hz}*t-{rad:.4})),2)':eval=frame[v{i}];",
+            i = i,
+            hz = params.rotation_hz,
+            rad = azimuth.to_radians(),
+        ));
+    }
+
+    // 3. Рендер в стерео
+    match render {
+        Binaural::Sofa(path) => {
+            graph.push_str(&join_speakers());
+            let speakers: Vec<String> = SPEAKERS
+                .iter()
+                .map(|(name, azimuth)| {
+                    let elevation = speaker_elevation(*azimuth, params.elevation_deg);
+                    format!("{} {:.0} {:.1}", name, azimuth, elevation)
+                })
+                .collect();
+            graph.push_str(&format!(
+                "sofalizer=sofa={}:type=freq:normalize=1:speakers={}",
+                escape_filter_path(path),
+                speakers.join("|")
+            ));
+        }
+        Binaural::Hrir(dir) => {
+            // Готовые HRIR лежат с шагом наклона TILT_STEP_DEG
+            let tilt = (params.elevation_deg / TILT_STEP_DEG).round() * TILT_STEP_DEG;
+            for (i, (name, azimuth)) in SPEAKERS.iter().enumerate() {
+                let hrir = hrir_path(dir, name, speaker_elevation(*azimuth, tilt));
+                graph.push_str(&format!(
+                    "amovie=filename={path}[h{i}];\
+                    [v{i}]pan=stereo|c0=c0|c1=c0[m{i}];[m{i}][h{i}]afir[b{i}];",
+                    path = escape_filter_path(&hrir),
+                    i = i,
+                ));
+            }
+            let inputs: String = (0..SPEAKERS.len()).map(|i| format!("[b{}]", i)).collect();
+            graph.push_str(&format!(
+                "{}amix=inputs={}:normalize=0",
+                inputs,
+                SPEAKERS.len()
+            ));
+        }
+        Binaural::Pan => {
+            graph.push_str(&join_speakers());
+            let (left, right): (Vec<String>, Vec<String>) = SPEAKERS
+                .iter()
+                .enumerate()
+                .map(|(i, (_, azimuth))| {
+                    let rad = azimuth.to_radians();
+                    // Тыловые колонки чуть тише — хоть какой-то намёк на "сзади"
+                    let depth = if rad.cos() < 0.0 { 0.7 } else { 1.0 };
+                    let l = ((1.0 + rad.sin()) / 2.0).sqrt() * depth;
+                    let r = ((1.0 - rad.sin()) / 2.0).sqrt() * depth;
+                    (format!("{:.3}*c{}", l, i), format!("{:.3}*c{}", r, i))
+                })
+                .unzip();
+            graph.push_str(&format!(
+                "pan=stereo|c0={}|c1={}",
+                left.join("+"),
+                right.join("+")
+            ));
+        }
+    }
+
+    // 4. Немного "комнаты", чтобы источник не звучал внутри головы
+    let reverb = params.reverb.clamp(0.0, 1.0);
+    if reverb > 0.0 {
+        graph.push_str(&format!(
+            ",aecho=0.9:0.9:37|71|113:{:.2}|{:.2}|{:.2}",
+            0.5 * reverb,
+            0.35 * reverb,
+            0.2 * reverb
+        ));
+    }
+
+    graph
+}
+
+// Колонки в один многоканальный поток octagonal
+fn join_speakers() -> String {
+    let inputs: String = (0..SPEAKERS.len()).map(|i| format!("[v{}]", i)).collect();
+    let map: Vec<String> = SPEAKERS
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| format!("{}.0-{}", i, name))
+        .collect();
+    format!(
+        "{}join=inputs={}:channel_layout=octagonal:map={},",
+        inputs,
+        SPEAKERS.len(),
+        map.join("|")
+    )
+}
+
+// Экранирование пути для аргумента фильтра (два уровня: опция и сам граф)
+pub fn escape_filter_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for ch in path.to_string_lossy().chars() {
+        match ch {
+            '\\' => escaped.push('/'),
+            ':' | '\'' => {
+                escaped.push_str("\\\\");
+                escaped.push(ch);
+            }
+            ',' | ';' | '[' | ']' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,164 @@
+use crate::domain::cover_art::{CoverImage, CropBox, ImageFormat, parse_cropdetect};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+// Сторона обложки по умолчанию: магнитолам и телефонам больше не нужно
+pub const DEFAULT_COVER_PX: u32 = 1000;
+
+// Перебирает превью по порядку, пока одно не удастся привести к обложке
+pub async fn fetch_cover(id: &str, candidates: &[String], max_px: u32) -> Option<CoverImage> {
+    for url in candidates {
+        let Some(bytes) = download(url).await else {
+            continue;
+        };
+        // Вместо картинки бывает HTML-заглушка или пустой ответ
+        let Some(format) = ImageFormat::detect(&bytes) else {
+            log::warn!("Превью {} — не картинка, пробую следующее", url);
+            continue;
+        };
+        match cover_from_bytes(id, &bytes, max_px).await {
+            Some(cover) => return Some(cover),
+            None => log::warn!("Не удалось обработать превью {} ({:?})", url, format),
+        }
+    }
+    None
+}
+
+// Обложка из готовой картинки (например, фото от пользователя)
+pub async fn cover_from_bytes(id: &str, bytes: &[u8], max_px: u32) -> Option<CoverImage> {
+    let format = ImageFormat::detect(bytes)?;
+    let jpeg = normalize(id, bytes, format, max_px).await?;
+    Some(CoverImage {
+        data: jpeg,
+        mime_type: ImageFormat::Jpeg.mime_type(),
+    })
+}
+
+// Обложка, вшитая в присланный файл (APIC, attached_pic), — в JPEG рядом с ботом
+pub async fn extract_embedded_cover(id: &str, source: &Path) -> Option<PathBuf> {
+    let target = PathBuf::from(format!("{}_embedded_cover.jpg", id));
+    let status = Command::new("ffmpeg")
+        .args(["-nostdin", "-loglevel", "error", "-i"])
+        .arg(source)
+        .args([
+            "-an",
+            "-map",
+            "0:v:0",
+            "-frames:v",
+            "1",
+            "-c:v",
+            "mjpeg",
+            "-y",
+        ])
+        .arg(&target)
+        .status()
+        .await
+        .ok()?;
+
+    if status.success() {
+        Some(target)
+    } else {
+        let _ = tokio::fs::remove_file(&target).await;
+        None
+    }
+}
+
+async fn download(url: &str) -> Option<Vec<u8>> {
+    let resp = reqwest::Client::new().get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.bytes().await.ok().map(|bytes| bytes.to_vec())
+}
+
+// Квадратная JPEG-обложка: срезаем чёрные поля (16:9 превью с "письмом"),
+// из оставшегося берём центральный квадрат и уменьшаем до max_px
+async fn normalize(id: &str, bytes: &[u8], format: ImageFormat, max_px: u32) -> Option<Vec<u8>> {
+    let source = PathBuf::from(format!("{}_cover_src.{}", id, format.extension()));
+    let target = PathBuf::from(format!("{}_cover_norm.jpg", id));
+    tokio::fs::write(&source, bytes).await.ok()?;
+
+    let mut filter = String::new();
+    if let Some(content) = detect_content(&source).await {
+        filter.push_str(&format!(
+            "crop={}:{}:{}:{},",
+            content.width, content.height, content.x, content.y
+        ));
+    }
+    filter.push_str(&format!(
+        "crop='min(iw,ih)':'min(iw,ih)',scale='min(iw,{0})':'min(ih,{0})'",
+        max_px
+    ));
+
+    let status = Command::new("ffmpeg")
+        .args(["-nostdin", "-loglevel", "error", "-i"])
+        .arg(&source)
+        .args([
+            "-vf",
+            &filter,
+            "-frames:v",
+            "1",
+            "-c:v",
+            "mjpeg",
+            "-q:v",
+            "2",
+            "-y",
+        ])
+        .arg(&target)
+        .status()
+        .await;
+
+    let result = match status {
+        Ok(status) if status.success() => tokio::fs::read(&target).await.ok(),
+        _ => None,
+    };
+    let _ = tokio::fs::remove_file(&source).await;
+    let _ = tokio::fs::remove_file(&target).await;
+    result
+}
+
+// cropdetect на нескольких копиях кадра: первые кадры он пропускает.
+// Слишком мелкую рамку отбрасываем — тогда берётся кадр целиком
+async fn detect_content(source: &Path) -> Option<CropBox> {
+    let (width, height) = image_size(source).await?;
+    let output = Command::new("ffmpeg")
+        .args(["-nostdin", "-hide_banner", "-loop", "1", "-i"])
+        .arg(source)
+        .args([
+            "-vf",
+            "cropdetect=limit=24:round=2",
+            "-frames:v",
+            "5",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .await
+        .ok()?;
+
+    parse_cropdetect(&String::from_utf8_lossy(&output.stderr))
+        .filter(|content| content.is_plausible(width, height))
+}
+
+async fn image_size(source: &Path) -> Option<(u32, u32)> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=width,height",
+            "-of",
+            "csv=p=0:s=x",
+        ])
+        .arg(source)
+        .output()
+        .await
+        .ok()?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (width, height) = text.trim().split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
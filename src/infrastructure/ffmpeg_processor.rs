@@ -1,40 +1,235 @@
-use crate::domain::audio_service::{AudioError, AudioMetadata, AudioPreset, AudioService};
+use crate::domain::audio_service::{
+    AudioError, AudioMetadata, AudioPreset, AudioService, CleanupStrength, CrossoverParams,
+    HighwayStrength, LoudnessTarget, MultichannelCodec, OutputFile, OutputMode, ProcessingOptions,
+    TempoParams, VirtualBassParams, VocalMode,
+};
+use crate::domain::audio_upload::uploaded_tags;
+use crate::domain::calibration::{
+    CabinProfile, CalibrationService, corrective_profile, measure_band_levels,
+};
+use crate::domain::driver_seat::DriverSeat;
+use crate::domain::eq::EqBand;
+use crate::domain::head_unit::transliterate;
+use crate::domain::lyrics::{Lyrics, parse_webvtt};
+use crate::domain::mono_check::{CorrelationMeter, MonoCheck};
+use crate::domain::music_analysis::{self, MusicalKey, estimate_bpm, estimate_key};
+use crate::domain::output_format::OutputFormat;
+use crate::domain::provenance::Provenance;
+use crate::domain::title_rules::TitleRules;
+use crate::domain::track_edges::{Fades, MIN_SILENCE_S, SilenceTrim, sound_bounds};
+use crate::domain::track_name::{TrackSource, parse_track_name};
+use crate::infrastructure::audio_probe::probe_audio;
+use crate::infrastructure::binaural::{Binaural, escape_filter_path, surround_filter};
+use crate::infrastructure::cover_art::{
+    DEFAULT_COVER_PX, cover_from_bytes, extract_embedded_cover, fetch_cover,
+};
+use crate::infrastructure::hrir::ensure_hrirs;
+use crate::infrastructure::impulse_responses::{ensure_impulse_responses, ir_path};
+use crate::infrastructure::video_info::fetch_video_info;
 use async_trait::async_trait;
-use id3::{Tag, TagLike, Version};
-use std::path::PathBuf;
+use id3::{Frame, Tag, TagLike, Timestamp, Version};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
 use uuid::Uuid;
 
-pub struct FFmpegProcessor;
+// HRTF по умолчанию (SOFA), если путь не задан через HRTF_SOFA_PATH
+const DEFAULT_HRTF_PATH: &str = "assets/hrtf/default.sofa";
+
+// Каталог импульсных откликов для реверба, если не задан через IR_DIR
+const DEFAULT_IR_DIR: &str = "assets/ir";
+
+// Свои HRIR для 8D без SOFA-файла, если каталог не задан через HRIR_DIR
+const DEFAULT_HRIR_DIR: &str = "assets/hrir";
+
+// Частота, в которой анализируем запись калибровки
+const ANALYSIS_RATE: u32 = 48000;
+
+// Тестовый сигнал идёт 20 с, дальше записи не читаем — длинная запись не съест память
+const MAX_RECORDING_S: f32 = 60.0;
+
+// Для проверки моно хватает и половины полосы — фазовые проблемы 8D ниже 11 кГц
+const MONO_CHECK_RATE: u32 = 22050;
+
+// Рабочая частота цепочки: loudnorm отдаёт 192 кГц, дальше нам столько не нужно
+const PROCESSING_RATE: u32 = 48000;
+
+pub struct FFmpegProcessor {
+    // HRTF для бинаурального 8D; None — рендерим 8D своими HRIR
+    pub hrtf_path: Option<PathBuf>,
+    // HRIR сферической головы для 8D без SOFA-файла
+    pub hrir_dir: PathBuf,
+    // Импульсные отклики комнат для свёрточного реверба
+    pub ir_dir: PathBuf,
+    // Что вырезать из названий роликов
+    pub title_rules: TitleRules,
+}
+
+impl FFmpegProcessor {
+    pub fn new() -> Self {
+        let hrtf_path = std::env::var("HRTF_SOFA_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_HRTF_PATH));
+
+        let hrtf_path = if hrtf_path.exists() {
+            Some(hrtf_path)
+        } else {
+            log::warn!(
+                "HRTF не найден ({}), 8D рендерим своими HRIR",
+                hrtf_path.display()
+            );
+            None
+        };
+
+        let ir_dir = std::env::var("IR_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_IR_DIR));
+        if let Err(e) = ensure_impulse_responses(&ir_dir) {
+            log::warn!(
+                "Не удалось подготовить импульсные отклики в {}: {}",
+                ir_dir.display(),
+                e
+            );
+        }
+
+        let hrir_dir = std::env::var("HRIR_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_HRIR_DIR));
+        if let Err(e) = ensure_hrirs(&hrir_dir) {
+            log::warn!(
+                "Не удалось подготовить HRIR в {}: {}",
+                hrir_dir.display(),
+                e
+            );
+        }
+
+        Self {
+            hrtf_path,
+            hrir_dir,
+            ir_dir,
+            title_rules: load_title_rules(),
+        }
+    }
+
+    // Полная цепочка: обрезка краёв, нормализация, эффекты по этапам, фейды,
+    // место водителя, коррекция салона. bounds — звучащая часть исходника, сек.
+    fn build_filter(&self, options: &ProcessingOptions, bounds: (f32, f32)) -> String {
+        let mut presets: Vec<&AudioPreset> = options.presets.iter().collect();
+        presets.sort_by_key(|p| p.stage());
+
+        // Громкость задаёт тональный пресет, иначе — стандарт стримингов
+        let loudness = presets
+            .iter()
+            .find_map(|p| p.loudness())
+            .unwrap_or_default();
+
+        let mut filter = String::new();
+        // Пустые границы — длительность так и не узнали, резать не по чему
+        if options.trim.is_some() && bounds.1 > bounds.0 {
+            filter.push_str(&format!(
+                "atrim=start={:.3}:end={:.3},asetpts=PTS-STARTPTS,",
+                bounds.0, bounds.1
+            ));
+        }
+        filter.push_str(&format!(
+            "{},aresample={}",
+            loudnorm_filter(&loudness),
+            PROCESSING_RATE
+        ));
+        for preset in presets {
+            let segment = self.preset_filter(preset, options.mono_safe);
+            if !segment.is_empty() {
+                filter.push(',');
+                filter.push_str(&segment);
+            }
+        }
+
+        // Фейды — после всех эффектов, иначе loudnorm и компрессор их "вытянут"
+        if let Some(fades) = &options.fades {
+            let tempo: f32 = options
+                .presets
+                .iter()
+                .map(AudioPreset::tempo_factor)
+                .product();
+            let length = (bounds.1 - bounds.0) / tempo;
+            if let Some(segment) = fades_filter(fades, length) {
+                filter.push(',');
+                filter.push_str(&segment);
+            }
+        }
+
+        if let Some(seat) = &options.seat {
+            filter.push(',');
+            filter.push_str(&seat_filter(seat));
+        }
+
+        if let Some(cabin) = &options.cabin
+            && !cabin.bands.is_empty()
+        {
+            // Коррекция может поднять пики — страхуемся лимитером
+            filter.push_str(&format!(
+                ",{},alimiter=limit=0.9:level=disabled",
+                eq_filter(&cabin.bands)
+            ));
+        }
+
+        filter
+    }
+
+    // Фильтр отдельного пресета (без нормализации — она общая на всю цепочку)
+    fn preset_filter(&self, preset: &AudioPreset, mono_safe: bool) -> String {
+        match preset {
+            AudioPreset::CarBass => "bass=g=3,treble=g=1".into(),
+            AudioPreset::PureHiFi => String::new(),
+            AudioPreset::ExtremeLow => "bass=g=6,treble=g=2".into(),
+            // HRTF разносит каналы по фазе — в моно это "дырявый" звук; панорама громкостью — нет
+            AudioPreset::Surround8D(params) => {
+                let render = match self.hrtf_path.as_deref() {
+                    _ if mono_safe => Binaural::Pan,
+                    Some(sofa) => Binaural::Sofa(sofa),
+                    None => Binaural::Hrir(&self.hrir_dir),
+                };
+                surround_filter(params, render)
+            }
+            // Полосы и preamp идут после loudnorm и могут поднять пики — ловим лимитером
+            AudioPreset::Custom(eq) => format!(
+                "volume={}dB,{},alimiter=limit=0.89:level=disabled",
+                eq.preamp,
+                eq_filter(&eq.bands)
+            ),
+            AudioPreset::StockSpeakers(params) => virtual_bass_filter(params),
+            AudioPreset::Tempo(params) => tempo_filter(params),
+            AudioPreset::Reverb(params) => {
+                reverb_filter(&ir_path(&self.ir_dir, params.room), params.mix)
+            }
+            AudioPreset::Limiter => "alimiter=limit=0.89:attack=5:release=50:level=disabled".into(),
+            AudioPreset::Highway(strength) => highway_filter(*strength),
+            AudioPreset::Vocals(mode) => vocals_filter(*mode),
+            AudioPreset::Cleanup(strength) => cleanup_filter(*strength),
+        }
+    }
+}
+
+impl Default for FFmpegProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[async_trait]
 impl AudioService for FFmpegProcessor {
     async fn process_track(
         &self,
         url: &str,
-        preset: AudioPreset,
-    ) -> Result<(PathBuf, AudioMetadata), AudioError> {
+        options: &ProcessingOptions,
+    ) -> Result<(Vec<OutputFile>, AudioMetadata), AudioError> {
         let id = Uuid::new_v4().to_string();
         let input = format!("{}_in.mp3", id);
-        let output = format!("{}_out.mp3", id);
-
-        // 1. Получаем расширенные метаданные (Title|Uploader|Thumbnail|Duration)
-        let info_output = Command::new("yt-dlp")
-            .args([
-                "--print",
-                "%(title)s|%(uploader)s|%(thumbnail)s|%(duration)s",
-                "--no-warnings",
-                url,
-            ])
-            .output()
-            .await
-            .map_err(|e| AudioError::DownloadError(e.to_string()))?;
-
-        let info_str = String::from_utf8_lossy(&info_output.stdout);
-        let parts: Vec<&str> = info_str.split('|').collect();
 
-        // Парсим длительность в секундах
-        let duration: u64 = parts.get(3).unwrap_or(&"0").trim().parse().unwrap_or(0);
+        // 1. Получаем метаданные ролика (JSON от yt-dlp)
+        let info = fetch_video_info(url).await?;
+        let duration = info.duration_secs();
 
         // ПРОВЕРКА ДЛИТЕЛЬНОСТИ (Лимит 45 минут = 2700 секунд)
         // Это важно, чтобы файл не превысил лимит Telegram в 50МБ при 320kbps
@@ -45,98 +240,1010 @@ impl AudioService for FFmpegProcessor {
             ));
         }
 
-        let metadata = AudioMetadata {
-            title: clean_title(parts.get(0).unwrap_or(&"Unknown Track")),
-            artist: parts.get(1).unwrap_or(&"Unknown Artist").trim().to_string(),
-            thumbnail_url: parts.get(2).map(|s| s.trim().to_string()),
-            duration, // Новое поле
+        let title = self.title_rules.clean(if info.title.is_empty() {
+            "Unknown Track"
+        } else {
+            &info.title
+        });
+        let name = parse_track_name(TrackSource {
+            title: &title,
+            channel: info.channel.as_deref().or(info.uploader.as_deref()),
+            artist: info.artist.as_deref(),
+            track: info.track.as_deref(),
+        });
+
+        let mut metadata = AudioMetadata {
+            title: name.tag_title(),
+            artist: name.artist,
+            album: info.album.clone(),
+            year: info.year(),
+            genre: info.genre(),
+            track: info.track_position(),
+            source_url: Some(info.webpage_url.clone().unwrap_or_else(|| url.to_string())),
+            // Исходное название: после чистки и разбора его уже не восстановить
+            comment: Some(match info.channel.as_deref().or(info.uploader.as_deref()) {
+                Some(channel) => format!("{} · {}", info.title, channel),
+                None => info.title.clone(),
+            }),
+            lyrics: None,
+            bpm: None,
+            key: None,
+            thumbnails: info.cover_candidates(),
+            duration,
         };
 
         // 2. Скачивание (Audio Only)
-        let dl_status = Command::new("yt-dlp")
-            .args(["-x", "--audio-format", "mp3", "-o", &input, url])
+        // Ручные субтитры на языке трека качаем заодно: из них выйдет текст песни
+        let subtitle_lang = info.subtitle_language();
+        let mut download = Command::new("yt-dlp");
+        // Ссылка с list= иначе скачала бы весь плейлист, а не ролик из тегов
+        download.args(["-x", "--audio-format", "mp3", "--no-playlist", "-o", &input]);
+        if let Some(lang) = &subtitle_lang {
+            // Своё имя для субтитров: от "-o ..._in.mp3" yt-dlp строит "..._in.mp3.ru.vtt"
+            let template = format!("subtitle:{}_sub.%(ext)s", id);
+            download.args(["--write-subs", "--sub-langs", lang, "--sub-format", "vtt"]);
+            download.args(["-o", &template]);
+        }
+        let dl_status = download
+            .arg(url)
             .status()
             .await
             .map_err(|e| AudioError::DownloadError(e.to_string()))?;
 
+        // Читаем и удаляем субтитры даже после неудачной загрузки — иначе копятся
+        if let Some(lang) = subtitle_lang {
+            metadata.lyrics = read_subtitles(&id, &lang).await;
+        }
+
         if !dl_status.success() {
             return Err(AudioError::DownloadError(
                 "Не удалось скачать аудио с YouTube".into(),
             ));
         }
 
-        // 3. Выбор фильтра в зависимости от пресета
-        let filter = match preset {
-            AudioPreset::CarBass => "loudnorm=I=-14:TP=-1.5:LRA=11,bass=g=3,treble=g=1",
-            AudioPreset::PureHiFi => "loudnorm=I=-16:TP=-1.5:LRA=11",
-            AudioPreset::ExtremeLow => "loudnorm=I=-12:TP=-1.0:LRA=11,bass=g=6,treble=g=2",
-            AudioPreset::Surround8D => "loudnorm=I=-14,apulsator=hz=0.1",
+        let result = self.render(&id, &input, metadata, options).await;
+        let _ = tokio::fs::remove_file(&input).await;
+        result
+    }
+
+    async fn process_upload(
+        &self,
+        path: &Path,
+        file_name: &str,
+        options: &ProcessingOptions,
+    ) -> Result<(Vec<OutputFile>, AudioMetadata), AudioError> {
+        let id = Uuid::new_v4().to_string();
+        let probe = probe_audio(path).await?;
+        let duration = probe.duration_secs();
+        if duration == 0 {
+            return Err(AudioError::DownloadError(
+                "Не похоже на аудиофайл — пришли MP3, M4A, FLAC или OGG".into(),
+            ));
+        }
+        if duration > 2700 {
+            return Err(AudioError::DownloadError(
+                "Трек слишком длинный (макс. 45 мин)".into(),
+            ));
+        }
+
+        let tags = uploaded_tags(|key| probe.tag(key), file_name, &self.title_rules);
+        let metadata = AudioMetadata {
+            title: tags.title,
+            artist: tags.artist,
+            album: tags.album,
+            year: tags.year,
+            genre: tags.genre,
+            track: tags.track,
+            source_url: None,
+            comment: tags.comment,
+            lyrics: None,
+            bpm: None,
+            key: None,
+            thumbnails: Vec::new(),
+            duration,
+        };
+
+        // Вшитая обложка переезжает в новый файл, если пользователь не дал свою
+        let mut options = options.clone();
+        let embedded = if options.custom_cover.is_none() {
+            extract_embedded_cover(&id, path).await
+        } else {
+            None
+        };
+        if embedded.is_some() {
+            options.custom_cover = embedded.clone();
+        }
+
+        let input = path.to_string_lossy();
+        let result = self.render(&id, &input, metadata, &options).await;
+        if let Some(cover) = embedded {
+            let _ = tokio::fs::remove_file(cover).await;
+        }
+        result
+    }
+
+    async fn read_provenance(&self, path: &Path) -> Option<Provenance> {
+        let probe = probe_audio(path).await.ok()?;
+        Provenance::from_tags(probe.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    // Моно-безопасный вариант отличается только 8D: панорама вместо HRTF/HRIR
+    fn has_mono_safe_variant(&self, presets: &[AudioPreset]) -> bool {
+        presets
+            .iter()
+            .any(|p| matches!(p, AudioPreset::Surround8D(_)))
+    }
+}
+
+impl FFmpegProcessor {
+    // Всё после получения исходника: эффекты, кодирование, обложка и теги
+    async fn render(
+        &self,
+        id: &str,
+        input: &str,
+        mut metadata: AudioMetadata,
+        options: &ProcessingOptions,
+    ) -> Result<(Vec<OutputFile>, AudioMetadata), AudioError> {
+        // yt-dlp знает длительность не всегда — тогда спрашиваем у ffprobe
+        if metadata.duration == 0 {
+            metadata.duration = probe_audio(Path::new(input))
+                .await
+                .map(|probe| probe.duration_secs())
+                .unwrap_or(0);
+        }
+        let duration = metadata.duration;
+
+        // Правки пользователя важнее того, что написано на YouTube
+        let edits = &options.tags;
+        if let Some(title) = &edits.title {
+            metadata.title = title.clone();
+        }
+        if let Some(artist) = &edits.artist {
+            metadata.artist = artist.clone();
+        }
+        if let Some(album) = &edits.album {
+            metadata.album = Some(album.clone());
+        }
+
+        // Slowed/nightcore меняют длительность — в подписи должна быть итоговая
+        let tempo: f32 = options
+            .presets
+            .iter()
+            .map(AudioPreset::tempo_factor)
+            .product();
+
+        // Звучащая часть трека: без тишины по краям, если пользователь включил /trim
+        let bounds = match &options.trim {
+            Some(trim) if duration > 0 => detect_sound_bounds(input, trim, duration as f32).await,
+            _ => (0.0, duration as f32),
+        };
+        metadata.duration = ((bounds.1 - bounds.0) / tempo).round() as u64;
+        metadata.lyrics = metadata
+            .lyrics
+            .map(|lyrics| lyrics.retimed(bounds.0, bounds.1, tempo));
+
+        // Темп и тональность меряем по исходнику и пересчитываем под slowed/nightcore:
+        // так они попадут в теги любого формата, которые пишет сам ffmpeg
+        if let Some((bpm, key)) = analyse_music(input, bounds).await {
+            let pitch: f32 = options
+                .presets
+                .iter()
+                .map(AudioPreset::pitch_factor)
+                .product();
+            metadata.bpm = bpm.map(|bpm| bpm * tempo);
+            metadata.key = key.map(|key| key.transposed((12.0 * pitch.log2()).round() as i32));
+        }
+
+        // 3. Граф фильтров: пресет + разводка по выходам
+        let filter = self.build_filter(options, bounds);
+        // Штатные магнитолы надёжно читают только MP3
+        let format = if options.head_unit.is_some() {
+            OutputFormat::Mp3Cbr
+        } else {
+            options.format
+        };
+        let (graph, pads) = output_graph(&filter, &options.output, format);
+
+        let mut outputs: Vec<OutputFile> = pads
+            .iter()
+            .map(|out| OutputFile {
+                path: PathBuf::from(format!("{}_{}.{}", id, out.pad, out.extension)),
+                suffix: out.suffix.map(String::from),
+                extension: out.extension,
+                mono: None,
+            })
+            .collect();
+
+        // Обложка нужна ещё до кодирования: в M4A и FLAC её вшивает сам ffmpeg
+        // Большую обложку штатная магнитола не покажет, а то и зависнет
+        let cover_px = options
+            .head_unit
+            .map_or(DEFAULT_COVER_PX, |mode| mode.cover_px);
+        let custom_cover = match &options.custom_cover {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => cover_from_bytes(id, &bytes, cover_px).await,
+                Err(_) => None,
+            },
+            None => None,
+        };
+        let cover = match custom_cover {
+            Some(image) => Some(image),
+            None => fetch_cover(id, &metadata.thumbnails, cover_px).await,
+        };
+        let cover_path = PathBuf::from(format!("{}_cover.jpg", id));
+        let cover_input = match &cover {
+            Some(image)
+                if pads
+                    .iter()
+                    .any(|p| p.tags == TagBackend::Container { cover: true }) =>
+            {
+                tokio::fs::write(&cover_path, &image.data).await.is_ok()
+            }
+            _ => false,
         };
 
         // 4. Обработка FFmpeg
-        let ff_status = Command::new("ffmpeg")
+        let mut ffmpeg = Command::new("ffmpeg");
+        ffmpeg.args(["-i", input]);
+        if cover_input {
+            ffmpeg.arg("-i").arg(&cover_path);
+        }
+        ffmpeg.args([
+            "-nostdin",
+            "-loglevel",
+            "error",
+            "-filter_complex",
+            &graph,
+            "-y",
+        ]);
+        for (out, file) in pads.iter().zip(&outputs) {
+            ffmpeg
+                .args(["-map", &format!("[{}]", out.pad)])
+                .args(out.encoder);
+            if options.head_unit.is_some() && out.tags == TagBackend::Id3 {
+                ffmpeg.args(["-ar", "44100"]);
+            }
+            // MP4-атомы, Vorbis comments и RIFF INFO пишет сам ffmpeg — ID3 туда не вписать
+            if let TagBackend::Container { cover } = out.tags {
+                for (key, value) in container_tags(&metadata) {
+                    ffmpeg.arg("-metadata").arg(format!("{}={}", key, value));
+                }
+                for (key, value) in options.provenance.tags() {
+                    ffmpeg.arg("-metadata").arg(format!("{}={}", key, value));
+                }
+                // Без этого флага MP4-муксер молча выбрасывает нестандартные ключи
+                if out.extension == "m4a" {
+                    ffmpeg.args(["-movflags", "+use_metadata_tags"]);
+                }
+                if cover && cover_input {
+                    ffmpeg.args([
+                        "-map",
+                        "1:v",
+                        "-c:v",
+                        "mjpeg",
+                        "-disposition:v:0",
+                        "attached_pic",
+                    ]);
+                }
+            }
+            ffmpeg.arg(&file.path);
+        }
+
+        let ff_status = ffmpeg
+            .status()
+            .await
+            .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+
+        if cover_input {
+            let _ = tokio::fs::remove_file(&cover_path).await;
+        }
+
+        if !ff_status.success() {
+            for file in &outputs {
+                let _ = tokio::fs::remove_file(&file.path).await;
+            }
+            return Err(AudioError::ProcessingError(
+                "Ошибка при обработке звука в FFmpeg".into(),
+            ));
+        }
+
+        // Как трек переживёт сложение в моно (одна колонка, моно-AUX)
+        for (out, file) in pads.iter().zip(outputs.iter_mut()) {
+            if out.check_mono {
+                match measure_mono(&file.path).await {
+                    Ok(check) => file.mono = Some(check),
+                    Err(e) => log::warn!("Не удалось проверить моно-совместимость: {}", e),
+                }
+            }
+        }
+
+        // 5. Вшиваем ID3 теги и Обложку в MP3
+        let mut tag = Tag::new();
+        tag.set_title(&metadata.title);
+        tag.set_artist(&metadata.artist);
+        if let Some(album) = &metadata.album {
+            tag.set_album(album);
+        }
+        if let Some(genre) = &metadata.genre {
+            tag.set_genre(genre);
+        }
+        if let Some(track) = metadata.track {
+            tag.set_track(track.number);
+            if let Some(total) = track.total {
+                tag.set_total_tracks(total);
+            }
+        }
+        if let Some(url) = &metadata.source_url {
+            tag.add_frame(Frame::link("WOAS", url));
+        }
+        for (description, value) in options.provenance.tags() {
+            tag.add_frame(id3::frame::ExtendedText {
+                description: description.to_string(),
+                value,
+            });
+        }
+        if let Some(bpm) = metadata.bpm {
+            tag.set_text("TBPM", format!("{:.0}", bpm));
+        }
+        if let Some(key) = metadata.key {
+            tag.set_text("TKEY", key.to_string());
+        }
+        // USLT читают почти все плееры, SYLT — те, что умеют караоке-прокрутку
+        if let Some(lyrics) = &metadata.lyrics {
+            tag.add_frame(id3::frame::Lyrics {
+                lang: lyrics.id3_language().to_string(),
+                description: String::new(),
+                text: lyrics.plain_text(),
+            });
+            tag.add_frame(id3::frame::SynchronisedLyrics {
+                lang: lyrics.id3_language().to_string(),
+                timestamp_format: id3::frame::TimestampFormat::Ms,
+                content_type: id3::frame::SynchronisedLyricsType::Lyrics,
+                description: String::new(),
+                content: lyrics
+                    .lines
+                    .iter()
+                    .map(|line| (line.start_ms, line.text.clone()))
+                    .collect(),
+            });
+        }
+        if let Some(comment) = &metadata.comment {
+            // Язык неизвестен, пока нет субтитров: "XXX" по стандарту ID3
+            let lang = metadata
+                .lyrics
+                .as_ref()
+                .map_or("XXX", |lyrics| lyrics.id3_language());
+            tag.add_frame(id3::frame::Comment {
+                lang: lang.to_string(),
+                description: String::new(),
+                text: comment.clone(),
+            });
+        }
+
+        if let Some(image) = cover {
+            tag.add_frame(id3::frame::Picture {
+                mime_type: image.mime_type.to_string(),
+                picture_type: id3::frame::PictureType::CoverFront,
+                description: "Cover".to_string(),
+                data: image.data,
+            });
+        }
+        // ID3v2.4 магнитолы часто не понимают: для них v2.3 и запасной v1 в конце файла
+        let version = if options.head_unit.is_some() {
+            Version::Id3v23
+        } else {
+            Version::Id3v24
+        };
+        // Год в v2.3 — TYER, в v2.4 — TDRC; сами id3 их не переводят
+        if let Some(year) = metadata.year {
+            match version {
+                Version::Id3v24 => tag.set_date_recorded(Timestamp {
+                    year,
+                    month: None,
+                    day: None,
+                    hour: None,
+                    minute: None,
+                    second: None,
+                }),
+                _ => tag.set_year(year),
+            }
+        }
+        for (out, file) in pads.iter().zip(&outputs) {
+            if out.tags == TagBackend::Id3 {
+                let _ = tag.write_to_path(&file.path, version);
+                if options.head_unit.is_some()
+                    && let Err(e) = append_id3v1(&file.path, &metadata).await
+                {
+                    log::warn!("Не удалось дописать ID3v1: {}", e);
+                }
+            }
+        }
+
+        Ok((outputs, metadata))
+    }
+}
+
+#[async_trait]
+impl CalibrationService for FFmpegProcessor {
+    async fn make_test_signal(&self) -> Result<PathBuf, AudioError> {
+        let output = PathBuf::from(format!("{}_calibration.mp3", Uuid::new_v4()));
+
+        // 2 секунды тишины (успеть положить телефон), затем 20 секунд розового шума
+        let status = Command::new("ffmpeg")
             .args([
-                "-i",
-                &input,
                 "-nostdin",
                 "-loglevel",
                 "error",
+                "-f",
+                "lavfi",
+                "-i",
+                "anoisesrc=color=pink:amplitude=0.5:duration=20:sample_rate=48000",
                 "-af",
-                filter,
+                "afade=t=in:d=0.5,afade=t=out:st=19.5:d=0.5,adelay=2000:all=1,pan=stereo|c0=c0|c1=c0",
                 "-b:a",
                 "320k",
                 "-y",
-                &output,
             ])
+            .arg(&output)
             .status()
             .await
             .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
 
-        let _ = tokio::fs::remove_file(&input).await;
-
-        if !ff_status.success() {
+        if !status.success() {
             return Err(AudioError::ProcessingError(
-                "Ошибка при обработке звука в FFmpeg".into(),
+                "Не удалось сгенерировать тестовый сигнал".into(),
             ));
         }
+        Ok(output)
+    }
 
-        // 5. Вшиваем ID3 теги и Обложку (асинхронно скачиваем картинку)
-        let mut tag = Tag::new();
-        tag.set_title(&metadata.title);
-        tag.set_artist(&metadata.artist);
+    async fn analyse_recording(&self, path: &Path) -> Result<CabinProfile, AudioError> {
+        let samples = decode_mono_pcm(path, ANALYSIS_RATE, Some((0.0, MAX_RECORDING_S))).await?;
+
+        let levels = measure_band_levels(&samples, ANALYSIS_RATE).ok_or_else(|| {
+            AudioError::ProcessingError("В записи не слышно тестового сигнала".into())
+        })?;
+
+        Ok(corrective_profile(&levels))
+    }
+}
+
+// Корреляция каналов готового файла; PCM читаем потоком, а не целиком
+async fn measure_mono(path: &Path) -> Result<MonoCheck, AudioError> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-nostdin", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args([
+            "-ac",
+            "2",
+            "-ar",
+            &MONO_CHECK_RATE.to_string(),
+            "-f",
+            "f32le",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| AudioError::ProcessingError("Нет вывода ffmpeg".into()))?;
+
+    let mut meter = CorrelationMeter::default();
+    let mut chunk = vec![0u8; 64 * 1024];
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        let read = stdout
+            .read(&mut chunk)
+            .await
+            .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        pending.extend_from_slice(&chunk[..read]);
+
+        // Кадр — два f32: левый и правый
+        let whole = pending.len() / 8 * 8;
+        for frame in pending[..whole].chunks_exact(8) {
+            let left = f32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]);
+            let right = f32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]);
+            meter.push(left, right);
+        }
+        pending.drain(..whole);
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+    if !status.success() {
+        return Err(AudioError::ProcessingError(
+            "Не удалось декодировать результат".into(),
+        ));
+    }
+    Ok(meter.finish())
+}
+
+// Сколько секунд из середины трека слушаем для темпа и тональности
+const MUSIC_EXCERPT_S: f32 = 120.0;
+
+async fn analyse_music(
+    input: &str,
+    bounds: (f32, f32),
+) -> Option<(Option<f32>, Option<MusicalKey>)> {
+    let length = (bounds.1 - bounds.0).min(MUSIC_EXCERPT_S);
+    // Длительность неизвестна — весь файл ради анализа не декодируем
+    if length <= 0.0 {
+        return None;
+    }
+    let start = bounds.0 + ((bounds.1 - bounds.0) - length) / 2.0;
+    let samples = match decode_mono_pcm(
+        Path::new(input),
+        music_analysis::ANALYSIS_RATE,
+        Some((start, length)),
+    )
+    .await
+    {
+        Ok(samples) => samples,
+        Err(e) => {
+            log::warn!("Не удалось декодировать трек для анализа темпа: {}", e);
+            return None;
+        }
+    };
+    // FFT по двум минутам — ощутимая работа, не занимаем поток рантайма
+    tokio::task::spawn_blocking(move || {
+        let rate = music_analysis::ANALYSIS_RATE;
+        (estimate_bpm(&samples, rate), estimate_key(&samples, rate))
+    })
+    .await
+    .ok()
+}
+
+// Декодирует любой файл в моно f32 PCM с заданной частотой.
+// excerpt — (начало, длина) в секундах, если нужен только кусок
+async fn decode_mono_pcm(
+    path: &Path,
+    sample_rate: u32,
+    excerpt: Option<(f32, f32)>,
+) -> Result<Vec<f32>, AudioError> {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-nostdin", "-loglevel", "error"]);
+    if let Some((start, length)) = excerpt {
+        command
+            .arg("-ss")
+            .arg(format!("{:.3}", start))
+            .arg("-t")
+            .arg(format!("{:.3}", length));
+    }
+    let output = command
+        .arg("-i")
+        .arg(path)
+        .args([
+            "-ac",
+            "1",
+            "-ar",
+            &sample_rate.to_string(),
+            "-f",
+            "f32le",
+            "-",
+        ])
+        .output()
+        .await
+        .map_err(|e| AudioError::ProcessingError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(AudioError::ProcessingError(
+            "Не удалось декодировать запись".into(),
+        ));
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+// Предварительный проход silencedetect. Не silenceremove прямо в графе:
+// для фейда в конце нужно заранее знать, где трек закончится.
+async fn detect_sound_bounds(input: &str, trim: &SilenceTrim, duration: f32) -> (f32, f32) {
+    let output = Command::new("ffmpeg")
+        .args(["-nostdin", "-hide_banner", "-nostats", "-i", input, "-af"])
+        .arg(format!(
+            "silencedetect=noise={}dB:d={}",
+            trim.threshold_db, MIN_SILENCE_S
+        ))
+        .args(["-f", "null", "-"])
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            sound_bounds(&String::from_utf8_lossy(&output.stderr), duration)
+        }
+        _ => {
+            log::warn!("silencedetect не отработал, трек не обрезаем");
+            (0.0, duration)
+        }
+    }
+}
 
-        if let Some(thumb_url) = &metadata.thumbnail_url {
-            let client = reqwest::Client::new();
-            if let Ok(resp) = client.get(thumb_url).send().await {
-                if let Ok(bytes) = resp.bytes().await {
-                    tag.add_frame(id3::frame::Picture {
-                        mime_type: "image/jpeg".to_string(),
-                        picture_type: id3::frame::PictureType::CoverFront,
-                        description: "Cover".to_string(),
-                        data: bytes.to_vec(),
-                    });
+// length — длина трека на выходе цепочки; без неё затухание в конце не поставить
+fn fades_filter(fades: &Fades, length: f32) -> Option<String> {
+    let mut chain = Vec::new();
+    if fades.fade_in_s > 0.0 {
+        chain.push(format!("afade=t=in:d={}", fades.fade_in_s));
+    }
+    if fades.fade_out_s > 0.0 && length > fades.fade_out_s {
+        chain.push(format!(
+            "afade=t=out:st={:.3}:d={}",
+            length - fades.fade_out_s,
+            fades.fade_out_s
+        ));
+    }
+    (!chain.is_empty()).then(|| chain.join(","))
+}
+
+fn loudnorm_filter(target: &LoudnessTarget) -> String {
+    format!("loudnorm=I={}:TP={}:LRA=11", target.lufs, target.true_peak)
+}
+
+// Теги для MP4, Vorbis comments и RIFF INFO — ffmpeg сам переводит ключи
+fn container_tags(metadata: &AudioMetadata) -> Vec<(&'static str, String)> {
+    let mut tags = vec![
+        ("title", metadata.title.clone()),
+        ("artist", metadata.artist.clone()),
+    ];
+    if let Some(album) = &metadata.album {
+        tags.push(("album", album.clone()));
+    }
+    if let Some(year) = metadata.year {
+        tags.push(("date", year.to_string()));
+    }
+    if let Some(genre) = &metadata.genre {
+        tags.push(("genre", genre.clone()));
+    }
+    if let Some(track) = metadata.track {
+        tags.push(("track", track.to_string()));
+    }
+    if let Some(comment) = &metadata.comment {
+        tags.push(("comment", comment.clone()));
+    }
+    if let Some(lyrics) = &metadata.lyrics {
+        tags.push(("lyrics", lyrics.plain_text()));
+    }
+    // Имена, которые читают Traktor, Rekordbox и foobar2000
+    if let Some(bpm) = metadata.bpm {
+        tags.push(("BPM", format!("{:.0}", bpm)));
+    }
+    if let Some(key) = metadata.key {
+        tags.push(("INITIALKEY", key.to_string()));
+    }
+    tags
+}
+
+// ID3v1 — 128 байт в конце файла, только латиница (Latin-1)
+async fn append_id3v1(path: &Path, metadata: &AudioMetadata) -> std::io::Result<()> {
+    let field = |text: &str, len: usize| {
+        let mut bytes: Vec<u8> = transliterate(text).bytes().take(len).collect();
+        bytes.resize(len, 0);
+        bytes
+    };
+
+    let mut tag = Vec::with_capacity(128);
+    tag.extend_from_slice(b"TAG");
+    tag.extend(field(&metadata.title, 30));
+    tag.extend(field(&metadata.artist, 30));
+    tag.extend(field(metadata.album.as_deref().unwrap_or(""), 30));
+    let year = metadata.year.map(|y| y.to_string()).unwrap_or_default();
+    tag.extend(field(&year, 4));
+    // ID3v1.1: комментарий 28 байт, ноль и номер трека
+    tag.extend(field(metadata.comment.as_deref().unwrap_or(""), 28));
+    tag.push(0);
+    tag.push(metadata.track.map_or(0, |t| t.number.min(255) as u8));
+    tag.push(255); // жанр из списка v1 не подбираем — он есть в v2
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .await?;
+    tokio::io::AsyncWriteExt::write_all(&mut file, &tag).await
+}
+
+// Кто пишет теги в файл: id3 после кодирования или ffmpeg при записи контейнера
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagBackend {
+    Id3,
+    // cover — умеет ли контейнер хранить обложку (MP4, FLAC — да; Opus, WAV, AC3 — нет)
+    Container { cover: bool },
+}
+
+// Выход графа: имя пэда, суффикс файла, кодирование и теги
+struct OutputPad {
+    pad: &'static str,
+    suffix: Option<&'static str>,
+    encoder: &'static [&'static str],
+    extension: &'static str,
+    tags: TagBackend,
+    // Стерео-выход, который стоит проверить на сложение в моно
+    check_mono: bool,
+}
+
+impl OutputPad {
+    fn stereo(pad: &'static str, suffix: Option<&'static str>, format: OutputFormat) -> Self {
+        let (encoder, tags): (&'static [&'static str], TagBackend) = match format {
+            OutputFormat::Mp3Cbr => (&["-c:a", "libmp3lame", "-b:a", "320k"], TagBackend::Id3),
+            OutputFormat::Mp3Vbr => (&["-c:a", "libmp3lame", "-q:a", "0"], TagBackend::Id3),
+            OutputFormat::Aac => (
+                &["-c:a", "aac", "-b:a", "256k"],
+                TagBackend::Container { cover: true },
+            ),
+            OutputFormat::Alac => (&["-c:a", "alac"], TagBackend::Container { cover: true }),
+            OutputFormat::Opus => (
+                &["-c:a", "libopus", "-b:a", "192k"],
+                TagBackend::Container { cover: false },
+            ),
+            OutputFormat::Flac => (&["-c:a", "flac"], TagBackend::Container { cover: true }),
+            OutputFormat::Wav => (
+                &["-c:a", "pcm_s16le"],
+                TagBackend::Container { cover: false },
+            ),
+        };
+        Self {
+            pad,
+            suffix,
+            encoder,
+            extension: format.extension(),
+            tags,
+            check_mono: true,
+        }
+    }
+}
+
+// Полный filter_complex и список выходных пэдов
+fn output_graph(filter: &str, mode: &OutputMode, format: OutputFormat) -> (String, Vec<OutputPad>) {
+    match mode {
+        OutputMode::Stereo => (
+            format!("[0:a]{}[out]", filter),
+            vec![OutputPad::stereo("out", None, format)],
+        ),
+        OutputMode::Crossover(params) => (
+            format!(
+                "[0:a]{},asplit=2[xo_sub][xo_main];\
+                [xo_sub]{},pan=mono|c0=0.5*c0+0.5*c1[sub];\
+                [xo_main]{}[mains]",
+                filter,
+                crossover_chain("lowpass", params),
+                crossover_chain("highpass", params)
+            ),
+            vec![
+                OutputPad::stereo("mains", Some("Mains"), format),
+                // Саб и так моно
+                OutputPad {
+                    check_mono: false,
+                    ..OutputPad::stereo("sub", Some("Sub"), format)
+                },
+            ],
+        ),
+        OutputMode::Surround(params) => {
+            let to_linear = |db: f32| 10f32.powf(db / 20.0);
+            let (encoder, tags): (&'static [&'static str], TagBackend) = match params.codec {
+                MultichannelCodec::Flac => {
+                    (&["-c:a", "flac"], TagBackend::Container { cover: true })
                 }
+                // 640 кбит/с — максимум AC3, который понимают магнитолы
+                MultichannelCodec::Ac3 => (
+                    &["-c:a", "ac3", "-b:a", "640k"],
+                    TagBackend::Container { cover: false },
+                ),
+            };
+            (
+                format!(
+                    "[0:a]{},surround=chl_out=5.1:fc_out={:.3}:lfe_out={:.3}[surround]",
+                    filter,
+                    to_linear(params.center_db),
+                    to_linear(params.lfe_db)
+                ),
+                vec![OutputPad {
+                    pad: "surround",
+                    suffix: Some("5.1"),
+                    encoder,
+                    extension: params.codec.extension(),
+                    tags,
+                    check_mono: false,
+                }],
+            )
+        }
+    }
+}
+
+// Каскад баттервортов 2-го порядка: 12 дБ/окт на звено,
+// два звена дают Linkwitz-Riley 24 дБ/окт (саб + фронт в сумме ровные)
+fn crossover_chain(kind: &str, params: &CrossoverParams) -> String {
+    let stages = (params.slope_db / 12).max(1) as usize;
+    vec![format!("{}=f={}:poles=2", kind, params.freq_hz); stages].join(",")
+}
+
+// Задержка и баланс каналов под место водителя, плюс ширина стерео через mid/side
+fn seat_filter(seat: &DriverSeat) -> String {
+    let (left_delay, right_delay) = seat.delays_ms();
+    let (left_gain, right_gain) = seat.gains_db();
+    let to_linear = |db: f32| 10f32.powf(db / 20.0);
+
+    let mut filter = format!(
+        "adelay=delays={:.3}|{:.3},pan=stereo|c0={:.4}*c0|c1={:.4}*c1",
+        left_delay,
+        right_delay,
+        to_linear(left_gain),
+        to_linear(right_gain)
+    );
+    if seat.width_percent != 100.0 {
+        filter.push_str(&format!(
+            ",stereotools=slev={:.3}",
+            (seat.width_percent / 100.0).max(0.015625)
+        ));
+    }
+    filter
+}
+
+// Темп: либо как на пластинке (меняется и высота), либо только скорость
+fn tempo_filter(params: &TempoParams) -> String {
+    if params.keep_pitch {
+        format!("atempo={}", params.factor)
+    } else {
+        format!(
+            "asetrate={}*{},aresample={}",
+            PROCESSING_RATE, params.factor, PROCESSING_RATE
+        )
+    }
+}
+
+// Свёрточный реверб: отклик подгружаем прямо в графе, сухой и мокрый сигнал смешиваем
+fn reverb_filter(ir: &Path, mix: f32) -> String {
+    let mix = mix.clamp(0.0, 1.0);
+    format!(
+        "asplit=2[rv_dry][rv_in];\
+        amovie=filename={}[rv_ir];\
+        [rv_in][rv_ir]afir[rv_wet];\
+        [rv_dry][rv_wet]amix=inputs=2:weights={:.2} {:.2}:normalize=0",
+        escape_filter_path(ir),
+        1.0 - mix,
+        mix
+    )
+}
+
+// Виртуальный бас: основная ветка без глубокого саба, вторая ветка — саб,
+// перегруженный через мягкий клиппер, от которого оставляем только гармоники
+fn virtual_bass_filter(params: &VirtualBassParams) -> String {
+    let cutoff = params.cutoff_hz;
+    format!(
+        "asplit=2[vb_main][vb_sub];\
+        [vb_sub]lowpass=f={c}:poles=2,lowpass=f={c}:poles=2,volume=4,asoftclip=type=tanh,\
+        highpass=f={c}:poles=2,highpass=f={c}:poles=2,lowpass=f={top}[vb_harm];\
+        [vb_main]highpass=f={c}:poles=2,highpass=f={c}:poles=2[vb_hp];\
+        [vb_hp][vb_harm]amix=inputs=2:weights=1 0.5:normalize=0,\
+        alimiter=limit=0.9:level=disabled",
+        c = cutoff,
+        top = cutoff * 4.0
+    )
+}
+
+// Щелчки убираем первыми — на них спотыкается шумодав. Дальше узкие вырезы
+// на гармониках 50 и 60 Гц (регион записи не знаем) и FFT-шумодав.
+// На сильном уровне добавляем anlmdn: медленный, но хорошо чистит шипение плёнки.
+fn cleanup_filter(strength: CleanupStrength) -> String {
+    let mut chain = vec!["adeclick".to_string()];
+    for mains in [50, 60] {
+        for harmonic in 1..=strength.hum_harmonics() {
+            chain.push(format!("equalizer=f={}:t=q:w=30:g=-30", mains * harmonic));
+        }
+    }
+    chain.push(format!(
+        "afftdn=nr={}:nf=-50:tn=1",
+        strength.noise_reduction_db()
+    ));
+    if strength == CleanupStrength::Strong {
+        chain.push("anlmdn=s=0.0001".into());
+    }
+    chain.join(",")
+}
+
+// Вокал обычно стоит ровно по центру, поэтому живёт в mid-канале.
+// Караоке глушит mid только выше 150 Гц: бас и бочка тоже в центре, их не трогаем.
+fn vocals_filter(mode: VocalMode) -> String {
+    match mode {
+        VocalMode::Instrumental => "asplit=2[kr_full][kr_mid];\
+            [kr_full]lowpass=f=150:poles=2,lowpass=f=150:poles=2[kr_low];\
+            [kr_mid]highpass=f=150:poles=2,highpass=f=150:poles=2,\
+            stereotools=mlev=0.015625[kr_side];\
+            [kr_low][kr_side]amix=inputs=2:normalize=0"
+            .into(),
+        VocalMode::VocalsOnly => "highpass=f=150:poles=2,highpass=f=150:poles=2,\
+            stereotools=slev=0.015625"
+            .into(),
+    }
+}
+
+// Четыре полосы с одной кривой, но своими временами: бас реагирует медленнее,
+// чтобы не "качался", верх — быстрее. Пики после подъёма ловит лимитер.
+// Аргументы в кавычках: внутри запятые, которые иначе разрежут граф.
+fn highway_filter(strength: HighwayStrength) -> String {
+    let points = strength
+        .transfer_points()
+        .iter()
+        .map(|(input, output)| format!("{}/{}", input, output))
+        .collect::<Vec<_>>()
+        .join(",");
+    let bands = [
+        ("0.02,0.3", 160),
+        ("0.01,0.2", 1200),
+        ("0.005,0.15", 6000),
+        ("0.003,0.1", 20000),
+    ]
+    .iter()
+    .map(|(times, crossover)| format!("{} 6 {} {}", times, points, crossover))
+    .collect::<Vec<_>>()
+    .join(" | ");
+
+    format!(
+        "mcompand=args='{}',alimiter=limit=0.89:attack=5:release=80:level=disabled",
+        bands
+    )
+}
+
+// Цепочка параметрических полос для ffmpeg
+fn eq_filter(bands: &[EqBand]) -> String {
+    bands
+        .iter()
+        .map(|b| format!("equalizer=f={}:t=q:w={}:g={}", b.freq, b.q, b.gain))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Субтитры, которые yt-dlp положил рядом с аудио: "{id}_sub.{lang}.vtt".
+// Язык в имени бывает не тот, что просили ("ru" -> "ru-RU"), поэтому берём
+// любой "{id}_sub.*.vtt" и удаляем всё, что нашлось
+async fn read_subtitles(id: &str, lang: &str) -> Option<Lyrics> {
+    let prefix = format!("{}_sub.", id);
+    let mut written = Vec::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(".").await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with(&prefix) {
+                written.push(entry.path());
             }
         }
-        let _ = tag.write_to_path(&output, Version::Id3v24);
+    }
 
-        Ok((PathBuf::from(output), metadata))
+    let mut vtt = None;
+    for path in written {
+        if vtt.is_none() && path.extension().is_some_and(|ext| ext == "vtt") {
+            vtt = tokio::fs::read_to_string(&path).await.ok();
+        }
+        let _ = tokio::fs::remove_file(&path).await;
     }
+
+    let lines = parse_webvtt(&vtt?);
+    (!lines.is_empty()).then(|| Lyrics {
+        language: lang.to_string(),
+        lines,
+    })
 }
 
-// Функция для очистки названий от мусора YouTube
-fn clean_title(title: &str) -> String {
-    title
-        .replace("(Official Video)", "")
-        .replace("(Official Audio)", "")
-        .replace("(Official Music Video)", "")
-        .replace("[Official Video]", "")
-        .replace("[HQ]", "")
-        .replace("(Lyric Video)", "")
-        .replace("[Lyrics]", "")
-        .replace("(High Quality)", "")
-        .replace("4K", "")
-        .replace("8K", "")
-        .trim()
-        .to_string()
+// Свои правила из TITLE_RULES_PATH; если файла нет или он битый — встроенные
+fn load_title_rules() -> TitleRules {
+    let Ok(path) = std::env::var("TITLE_RULES_PATH") else {
+        return TitleRules::default();
+    };
+    let rules = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| TitleRules::parse(&text).map_err(|e| e.to_string()));
+    match rules {
+        Ok(rules) => rules,
+        Err(e) => {
+            log::warn!("Правила названий из {} не загружены: {}", path, e);
+            TitleRules::default()
+        }
+    }
 }
@@ -0,0 +1,115 @@
+use crate::infrastructure::binaural::{SPEAKERS, hrir_path, speaker_elevation};
+use crate::infrastructure::impulse_responses::write_wav;
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+
+const SAMPLE_RATE: u32 = 48000;
+
+// Длина HRIR: 5 мс хватает на задержку между ушами и отражения от ушной раковины
+const LEN: usize = 256;
+
+// Сферическая голова (Brown, Duda): радиус и скорость звука
+const HEAD_RADIUS_M: f32 = 0.0875;
+const SOUND_SPEED: f32 = 343.0;
+
+// Отражения ушной раковины: (сила, A, B, D), задержки в отсчётах при 44.1 кГц
+const PINNA_ECHOES: [(f32, f32, f32, f32); 5] = [
+    (0.5, 1.0, 2.0, 0.85),
+    (-1.0, 5.0, 4.0, 0.35),
+    (0.5, 5.0, 7.0, 0.35),
+    (-0.25, 5.0, 11.0, 0.35),
+    (0.25, 5.0, 13.0, 0.35),
+];
+
+// Шаг наклона кольца, для которого лежат готовые HRIR (наклон в /8d — от 0 до 60°)
+pub const TILT_STEP_DEG: f32 = 10.0;
+const MAX_TILT_DEG: f32 = 60.0;
+
+// Свои HRIR на случай, когда SOFA-файла нет: по файлу на каждую колонку кольца
+// и каждый шаг наклона. Недостающие синтезируем, лежащие не трогаем.
+pub fn ensure_hrirs(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let steps = (MAX_TILT_DEG / TILT_STEP_DEG) as usize;
+    for (name, azimuth) in SPEAKERS {
+        for step in 0..=steps {
+            let elevation = speaker_elevation(azimuth, step as f32 * TILT_STEP_DEG);
+            let path = hrir_path(dir, name, elevation);
+            if !path.exists() {
+                write_wav(&path, &synthesize(azimuth, elevation))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Модель сферической головы: задержка до каждого уха, тень головы на верхах
+// и пара отражений от ушной раковины, по которым слышно "спереди/сзади" и высоту
+fn synthesize(azimuth: f32, elevation: f32) -> [Vec<f32>; 2] {
+    let (az, el) = (azimuth.to_radians(), elevation.to_radians());
+    // Проекция направления на ось ушей: +1 — прямо в левое ухо
+    let lateral = el.cos() * az.sin();
+    // Угол от фронта для ушной раковины: 0 — спереди, 180° — сзади
+    let front = el.cos() * az.cos();
+    let from_front = front.clamp(-1.0, 1.0).acos();
+
+    [lateral, -lateral].map(|toward_ear| {
+        let incidence = toward_ear.clamp(-1.0, 1.0).acos();
+        let mut ear = vec![0.0f32; LEN];
+
+        // Сначала прямой звук и отражения, потом тень головы поверх всего
+        let onset = 2.0 + itd_s(incidence) * SAMPLE_RATE as f32;
+        add_impulse(&mut ear, onset, 1.0);
+        for (gain, a, b, d) in PINNA_ECHOES {
+            let delay = a * (from_front / 2.0).cos() * (d * (PI / 2.0 - el)).sin() + b;
+            add_impulse(&mut ear, onset + delay * SAMPLE_RATE as f32 / 44100.0, gain);
+        }
+        head_shadow(&mut ear, incidence);
+
+        for sample in ear.iter_mut() {
+            *sample *= 0.25;
+        }
+        ear
+    })
+}
+
+// Путь в обход головы: до ближнего уха короче, до дальнего — по дуге
+fn itd_s(incidence: f32) -> f32 {
+    let path = if incidence < PI / 2.0 {
+        1.0 - incidence.cos()
+    } else {
+        1.0 + incidence - PI / 2.0
+    };
+    HEAD_RADIUS_M / SOUND_SPEED * path
+}
+
+// Однополюсный фильтр тени: ближнее ухо получает подъём верхов до +6 дБ,
+// дальнее — завал. Билинейное преобразование аналогового прототипа.
+fn head_shadow(signal: &mut [f32], incidence: f32) {
+    let alpha = 1.05 + 0.95 * (incidence.to_degrees() / 150.0 * PI).cos();
+    let w0 = SOUND_SPEED / HEAD_RADIUS_M;
+    let k = SAMPLE_RATE as f32 / w0;
+    let (b0, b1) = (1.0 + alpha * k, 1.0 - alpha * k);
+    let (a0, a1) = (1.0 + k, 1.0 - k);
+
+    let (mut x1, mut y1) = (0.0, 0.0);
+    for sample in signal.iter_mut() {
+        let x = *sample;
+        let y = (b0 * x + b1 * x1 - a1 * y1) / a0;
+        x1 = x;
+        y1 = y;
+        *sample = y;
+    }
+}
+
+// Импульс с дробной задержкой: делим его между двумя соседними отсчётами
+fn add_impulse(signal: &mut [f32], position: f32, gain: f32) {
+    let index = position.floor() as usize;
+    let frac = position - position.floor();
+    if let Some(sample) = signal.get_mut(index) {
+        *sample += gain * (1.0 - frac);
+    }
+    if let Some(sample) = signal.get_mut(index + 1) {
+        *sample += gain * frac;
+    }
+}
@@ -0,0 +1,156 @@
+use crate::domain::audio_service::Room;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SAMPLE_RATE: u32 = 48000;
+
+// Параметры синтеза отклика: время затухания, предзадержка,
+// насколько быстрее гаснут верха и ранние отражения (мс, громкость)
+struct RoomDesign {
+    rt60: f32,
+    predelay_ms: f32,
+    hf_decay_ratio: f32,
+    early: &'static [(f32, f32)],
+}
+
+fn design(room: Room) -> RoomDesign {
+    match room {
+        Room::Plate => RoomDesign {
+            rt60: 1.8,
+            predelay_ms: 10.0,
+            hf_decay_ratio: 0.6,
+            early: &[],
+        },
+        // Концертный зал: длинный тёплый хвост, отражения от боковых стен
+        Room::Hall => RoomDesign {
+            rt60: 2.4,
+            predelay_ms: 25.0,
+            hf_decay_ratio: 0.5,
+            early: &[(12.0, 0.45), (19.0, 0.35), (27.0, 0.3), (38.0, 0.2)],
+        },
+        // Клуб: короткий и глухой — много людей и мягкой отделки
+        Room::Club => RoomDesign {
+            rt60: 0.9,
+            predelay_ms: 6.0,
+            hf_decay_ratio: 0.4,
+            early: &[(4.0, 0.55), (9.0, 0.4), (15.0, 0.25)],
+        },
+        // Собор: камень, очень долгий хвост и далёкие стены
+        Room::Cathedral => RoomDesign {
+            rt60: 5.0,
+            predelay_ms: 45.0,
+            hf_decay_ratio: 0.45,
+            early: &[(30.0, 0.35), (55.0, 0.3), (85.0, 0.25)],
+        },
+        // Маленькая комната: почти без хвоста, плотные близкие отражения
+        Room::Small => RoomDesign {
+            rt60: 0.35,
+            predelay_ms: 2.0,
+            hf_decay_ratio: 0.7,
+            early: &[(2.5, 0.7), (4.5, 0.5), (7.0, 0.4), (10.0, 0.3)],
+        },
+    }
+}
+
+pub fn ir_path(dir: &Path, room: Room) -> PathBuf {
+    dir.join(format!("{}.wav", room.name()))
+}
+
+// Готовые отклики лежат в assets/ir; недостающие (например, в своём IR_DIR)
+// синтезируем тем же кодом. Уже лежащий файл — в том числе запись настоящего зала — не трогаем.
+pub fn ensure_impulse_responses(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for room in Room::ALL {
+        let path = ir_path(dir, room);
+        if !path.exists() {
+            write_wav(&path, &synthesize(&design(room)))?;
+        }
+    }
+    Ok(())
+}
+
+// Стерео-хвост из затухающего шума: у каналов разный шум, поэтому звук получается широким.
+// Шум делится на низ и верх, верх гаснет быстрее — как в реальной комнате.
+fn synthesize(room: &RoomDesign) -> [Vec<f32>; 2] {
+    let predelay = (room.predelay_ms / 1000.0 * SAMPLE_RATE as f32) as usize;
+    let len = predelay + (room.rt60 * 1.2 * SAMPLE_RATE as f32) as usize;
+
+    let mut channels = [vec![0.0f32; len], vec![0.0f32; len]];
+    for (ch, out) in channels.iter_mut().enumerate() {
+        let mut rng = XorShift(0x9E37_79B9 + ch as u32 * 7919 + room.rt60.to_bits());
+        let mut low = 0.0f32;
+
+        for (i, sample) in out.iter_mut().enumerate().skip(predelay) {
+            let t = (i - predelay) as f32 / SAMPLE_RATE as f32;
+            let noise = rng.next_f32();
+            low += 0.2 * (noise - low);
+            let high = noise - low;
+
+            // -60 дБ за rt60 секунд
+            let low_env = (-6.91 * t / room.rt60).exp();
+            let high_env = (-6.91 * t / (room.rt60 * room.hf_decay_ratio)).exp();
+            *sample = low * low_env + high * high_env;
+        }
+
+        // Ранние отражения: в левом и правом канале чуть разнесены по времени
+        for (k, (ms, gain)) in room.early.iter().enumerate() {
+            let spread = if (k + ch) % 2 == 0 { 1.0 } else { 1.07 };
+            let pos = predelay + (ms * spread / 1000.0 * SAMPLE_RATE as f32) as usize;
+            if let Some(sample) = out.get_mut(pos) {
+                *sample += gain;
+            }
+        }
+    }
+
+    let peak = channels
+        .iter()
+        .flatten()
+        .fold(0.0f32, |acc, s| acc.max(s.abs()));
+    if peak > 0.0 {
+        for sample in channels.iter_mut().flatten() {
+            *sample *= 0.9 / peak;
+        }
+    }
+    channels
+}
+
+// 16-битный PCM WAV, стерео 48 кГц
+pub fn write_wav(path: &Path, channels: &[Vec<f32>; 2]) -> io::Result<()> {
+    let frames = channels[0].len();
+    let data_len = (frames * 2 * 2) as u32;
+
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // стерео
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 4).to_le_bytes());
+    bytes.extend_from_slice(&4u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+
+    for i in 0..frames {
+        for channel in channels {
+            let sample = (channel[i].clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    std::fs::write(path, bytes)
+}
+
+// Детерминированный шум: отклики одинаковые от запуска к запуску
+struct XorShift(u32);
+
+impl XorShift {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
@@ -0,0 +1,71 @@
+use crate::domain::job_repository::{JobRepository, PendingJob};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Брошенные задания (пользователь так и не нажал "Погнали") живут сутки
+const JOB_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Задания хранятся в памяти: после рестарта пользователь просто пришлёт ссылку снова
+#[derive(Default)]
+pub struct InMemoryJobRepo {
+    jobs: Mutex<HashMap<String, (Instant, PendingJob)>>,
+}
+
+impl InMemoryJobRepo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl JobRepository for InMemoryJobRepo {
+    async fn create(&self, job: PendingJob) -> String {
+        let id = Uuid::new_v4().simple().to_string()[..10].to_string();
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(|_, (created, _)| created.elapsed() < JOB_TTL);
+        jobs.insert(id.clone(), (Instant::now(), job));
+        id
+    }
+
+    async fn get(&self, job_id: &str) -> Option<PendingJob> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(job_id).map(|(_, job)| job.clone())
+    }
+
+    async fn update(&self, job_id: &str, job: PendingJob) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(entry) = jobs.get_mut(job_id) {
+            entry.1 = job;
+        }
+    }
+
+    async fn remove(&self, job_id: &str) -> Option<PendingJob> {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.remove(job_id).map(|(_, job)| job)
+    }
+
+    async fn restore(&self, job_id: &str, job: PendingJob) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.insert(job_id.to_string(), (Instant::now(), job));
+    }
+
+    async fn find_editing(&self, user_id: i64) -> Option<(String, PendingJob)> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter()
+            .filter(|(_, (_, job))| job.user_id == user_id && job.editing_tags)
+            .max_by_key(|(_, (created, _))| *created)
+            .map(|(id, (_, job))| (id.clone(), job.clone()))
+    }
+
+    async fn stop_editing(&self, user_id: i64) {
+        let mut jobs = self.jobs.lock().unwrap();
+        for (_, job) in jobs.values_mut() {
+            if job.user_id == user_id {
+                job.editing_tags = false;
+            }
+        }
+    }
+}
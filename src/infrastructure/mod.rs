@@ -1,2 +1,10 @@
+pub mod audio_probe;
+pub mod binaural;
+pub mod cover_art;
 pub mod ffmpeg_processor;
+pub mod hrir;
+pub mod impulse_responses;
+pub mod memory_job_repo;
+pub mod sqlite_settings_repo;
 pub mod sqlite_user_repo;
+pub mod video_info;
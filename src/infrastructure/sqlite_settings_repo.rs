@@ -0,0 +1,168 @@
+use crate::domain::audio_service::OutputMode;
+use crate::domain::output_format::OutputFormat;
+use crate::domain::settings_repository::{SettingsRepository, UserSettings};
+use async_trait::async_trait;
+use sqlx::{Row, SqlitePool};
+
+// Ключи в таблице user_settings (user_id, key, value).
+// Каждая настройка хранится строкой в своём текстовом формате —
+// новые настройки не требуют миграций схемы.
+const CUSTOM_EQ: &str = "custom_eq";
+const CABIN_PROFILE: &str = "cabin_profile";
+const AWAITING_CALIBRATION: &str = "awaiting_calibration";
+const DRIVER_SEAT: &str = "driver_seat";
+const SPEAKER_SIZE: &str = "speaker_size_cm";
+const SURROUND_8D: &str = "surround_8d";
+const REVERB_MIX: &str = "reverb_mix";
+const CROSSOVER: &str = "crossover";
+const SURROUND: &str = "surround";
+const OUTPUT_FORMAT: &str = "output_format";
+const HEAD_UNIT: &str = "head_unit";
+const SILENCE_TRIM: &str = "silence_trim";
+const FADES: &str = "fades";
+
+pub struct SqliteSettingsRepo {
+    pub pool: SqlitePool,
+}
+
+impl SqliteSettingsRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+// Раскладываем настройки в пары ключ/значение (None — удалить запись)
+fn to_entries(settings: &UserSettings) -> Vec<(&'static str, Option<String>)> {
+    vec![
+        (
+            CUSTOM_EQ,
+            settings.custom_eq.as_ref().map(|eq| eq.to_string()),
+        ),
+        (
+            CABIN_PROFILE,
+            settings.cabin_profile.as_ref().map(|p| p.to_string()),
+        ),
+        (
+            AWAITING_CALIBRATION,
+            settings.awaiting_calibration.then(|| "1".to_string()),
+        ),
+        (
+            DRIVER_SEAT,
+            settings.driver_seat.as_ref().map(|s| s.to_string()),
+        ),
+        (
+            SURROUND_8D,
+            settings.surround_8d.map(|params| params.to_string()),
+        ),
+        (REVERB_MIX, settings.reverb_mix.map(|mix| mix.to_string())),
+        (
+            SPEAKER_SIZE,
+            settings.speaker_size_cm.map(|size| size.to_string()),
+        ),
+        (
+            CROSSOVER,
+            match settings.output_mode {
+                OutputMode::Crossover(params) => Some(params.to_string()),
+                _ => None,
+            },
+        ),
+        (
+            SURROUND,
+            match settings.output_mode {
+                OutputMode::Surround(params) => Some(params.to_string()),
+                _ => None,
+            },
+        ),
+        (
+            SILENCE_TRIM,
+            settings.silence_trim.map(|trim| trim.to_string()),
+        ),
+        (FADES, settings.fades.map(|fades| fades.to_string())),
+        (
+            OUTPUT_FORMAT,
+            // Формат по умолчанию не храним
+            (settings.output_format != OutputFormat::default())
+                .then(|| settings.output_format.to_string()),
+        ),
+        (HEAD_UNIT, settings.head_unit.map(|mode| mode.to_string())),
+    ]
+}
+
+fn apply_entry(settings: &mut UserSettings, key: &str, value: &str) {
+    match key {
+        CUSTOM_EQ => settings.custom_eq = value.parse().ok(),
+        CABIN_PROFILE => settings.cabin_profile = value.parse().ok(),
+        AWAITING_CALIBRATION => settings.awaiting_calibration = value == "1",
+        DRIVER_SEAT => settings.driver_seat = value.parse().ok(),
+        SPEAKER_SIZE => settings.speaker_size_cm = value.parse().ok(),
+        SURROUND_8D => settings.surround_8d = value.parse().ok(),
+        REVERB_MIX => settings.reverb_mix = value.parse().ok(),
+        CROSSOVER => {
+            if let Ok(params) = value.parse() {
+                settings.output_mode = OutputMode::Crossover(params);
+            }
+        }
+        SURROUND => {
+            if let Ok(params) = value.parse() {
+                settings.output_mode = OutputMode::Surround(params);
+            }
+        }
+        SILENCE_TRIM => settings.silence_trim = value.parse().ok(),
+        FADES => settings.fades = value.parse().ok(),
+        OUTPUT_FORMAT => settings.output_format = value.parse().unwrap_or_default(),
+        HEAD_UNIT => settings.head_unit = value.parse().ok(),
+        _ => {}
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for SqliteSettingsRepo {
+    async fn get_settings(&self, user_id: i64) -> UserSettings {
+        let rows = sqlx::query("SELECT key, value FROM user_settings WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_default();
+
+        let mut settings = UserSettings::default();
+        for row in rows {
+            let key: String = row.get(0);
+            let value: String = row.get(1);
+            apply_entry(&mut settings, &key, &value);
+        }
+        settings
+    }
+
+    async fn save_settings(
+        &self,
+        user_id: i64,
+        settings: &UserSettings,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for (key, value) in to_entries(settings) {
+            match value {
+                Some(value) => {
+                    sqlx::query(
+                        "INSERT INTO user_settings (user_id, key, value) VALUES (?, ?, ?)
+                         ON CONFLICT(user_id, key) DO UPDATE SET value = excluded.value",
+                    )
+                    .bind(user_id)
+                    .bind(key)
+                    .bind(value)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query("DELETE FROM user_settings WHERE user_id = ? AND key = ?")
+                        .bind(user_id)
+                        .bind(key)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        tx.commit().await
+    }
+}
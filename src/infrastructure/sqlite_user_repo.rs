@@ -34,13 +34,15 @@ impl UserRepository for SqliteUserRepo {
         }
     }
 
-    async fn use_credit(&self, user_id: i64) -> bool {
-        // ИСПОЛЬЗУЕМ self.pool для обновления
-        let result =
-            sqlx::query("UPDATE users SET balance = balance - 1 WHERE user_id = ? AND balance > 0")
-                .bind(user_id)
-                .execute(&self.pool)
-                .await;
+    async fn use_credits(&self, user_id: i64, amount: i32) -> bool {
+        let result = sqlx::query(
+            "UPDATE users SET balance = balance - ? WHERE user_id = ? AND balance >= ?",
+        )
+        .bind(amount)
+        .bind(user_id)
+        .bind(amount)
+        .execute(&self.pool)
+        .await;
 
         match result {
             Ok(res) => res.rows_affected() > 0,
@@ -0,0 +1,171 @@
+use crate::domain::audio_service::AudioError;
+use crate::domain::cover_art::{ThumbnailCandidate, rank_thumbnails};
+use crate::domain::lyrics::pick_subtitle_language;
+use crate::domain::track_tags::{
+    TrackPosition, detect_genre, playlist_id, playlist_position, release_year,
+};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
+use tokio::process::Command;
+
+// Нужная нам часть JSON, который отдаёт `yt-dlp -J`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct VideoInfo {
+    pub id: String,
+    pub title: String,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    // Есть у музыкальных релизов (YouTube Music, "Provided to YouTube by ...")
+    pub artist: Option<String>,
+    pub track: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub release_year: Option<i32>,
+    // "20240131"
+    pub upload_date: Option<String>,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+    pub track_number: Option<u32>,
+    // С --no-playlist не приходят — заполняем сами по list= из ссылки
+    pub playlist_index: Option<u32>,
+    pub playlist_count: Option<u32>,
+    pub webpage_url: Option<String>,
+    // Язык ролика, если автор его указал
+    pub language: Option<String>,
+    // Ручные субтитры по языкам (автоматические лежат в automatic_captions)
+    pub subtitles: HashMap<String, Vec<IgnoredAny>>,
+    // Секунды; у трансляций и части площадок может не быть
+    pub duration: Option<f64>,
+    pub thumbnail: Option<String>,
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+// Ответ `yt-dlp -J --flat-playlist`: только id роликов, без их страниц
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FlatPlaylist {
+    entries: Vec<FlatEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FlatEntry {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub preference: Option<i32>,
+}
+
+impl VideoInfo {
+    pub fn duration_secs(&self) -> u64 {
+        self.duration.unwrap_or(0.0).max(0.0).round() as u64
+    }
+
+    pub fn year(&self) -> Option<i32> {
+        release_year(self.release_year, self.upload_date.as_deref())
+    }
+
+    pub fn genre(&self) -> Option<String> {
+        detect_genre(self.genre.as_deref(), &self.tags, &self.categories)
+    }
+
+    // Номер из релиза, иначе позиция в плейлисте
+    pub fn track_position(&self) -> Option<TrackPosition> {
+        let (number, total) = match (self.track_number, self.playlist_index) {
+            (Some(number), _) => (number, None),
+            (None, Some(index)) => (index, self.playlist_count),
+            (None, None) => return None,
+        };
+        (number > 0).then_some(TrackPosition { number, total })
+    }
+
+    // Язык субтитров, из которых возьмём текст песни
+    pub fn subtitle_language(&self) -> Option<String> {
+        let mut available: Vec<&str> = self
+            .subtitles
+            .keys()
+            .map(String::as_str)
+            .filter(|lang| *lang != "live_chat")
+            .collect();
+        available.sort_unstable();
+        pick_subtitle_language(self.language.as_deref(), &available)
+    }
+
+    // Превью в порядке, в котором их стоит пробовать для обложки
+    pub fn cover_candidates(&self) -> Vec<String> {
+        let candidates: Vec<ThumbnailCandidate> = self
+            .thumbnails
+            .iter()
+            .map(|t| ThumbnailCandidate {
+                url: t.url.clone(),
+                width: t.width,
+                height: t.height,
+                preference: t.preference,
+            })
+            .collect();
+
+        let mut urls = rank_thumbnails(&candidates);
+        // Основное превью — последний шанс, если весь список не подошёл
+        if let Some(main) = &self.thumbnail
+            && !urls.contains(main)
+        {
+            urls.push(main.clone());
+        }
+        urls
+    }
+}
+
+pub async fn fetch_video_info(url: &str) -> Result<VideoInfo, AudioError> {
+    let output = Command::new("yt-dlp")
+        .args(["-J", "--no-playlist", "--no-warnings", url])
+        .output()
+        .await
+        .map_err(|e| AudioError::DownloadError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(AudioError::DownloadError(
+            "Не удалось получить информацию о видео".into(),
+        ));
+    }
+
+    let mut info: VideoInfo = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AudioError::DownloadError(format!("Непонятный ответ yt-dlp: {}", e)))?;
+
+    // Ролик открыт из плейлиста (альбома) — номер трека берём из его порядка
+    if info.playlist_index.is_none()
+        && let Some(list) = playlist_id(url)
+        && let Some(position) = playlist_position(&info.id, &fetch_playlist_ids(&list).await)
+    {
+        info.playlist_index = Some(position.number);
+        info.playlist_count = position.total;
+    }
+    Ok(info)
+}
+
+// Id роликов плейлиста по порядку; без сети или на битом ответе — пусто
+async fn fetch_playlist_ids(list: &str) -> Vec<String> {
+    let url = format!("https://www.youtube.com/playlist?list={}", list);
+    let output = Command::new("yt-dlp")
+        .args(["-J", "--flat-playlist", "--no-warnings", &url])
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            serde_json::from_slice::<FlatPlaylist>(&output.stdout)
+                .map(|playlist| playlist.entries.into_iter().map(|e| e.id).collect())
+                .unwrap_or_default()
+        }
+        _ => {
+            log::warn!("Не удалось получить плейлист {}", list);
+            Vec::new()
+        }
+    }
+}
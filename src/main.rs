@@ -1,12 +1,29 @@
 mod domain;
 mod infrastructure;
 
-use crate::domain::audio_service::{AudioPreset, AudioService};
+use crate::domain::audio_service::{
+    AudioError, AudioService, CrossoverParams, OutputMode, ProcessingOptions, SurroundParams,
+    UpmixParams, VirtualBassParams,
+};
+use crate::domain::audio_upload::{MAX_UPLOAD_BYTES, upload_extension};
+use crate::domain::calibration::CalibrationService;
+use crate::domain::driver_seat::DriverSeat;
+use crate::domain::eq::{CustomEq, MAX_EQ_BANDS};
+use crate::domain::head_unit::{HeadUnitMode, safe_file_name};
+use crate::domain::job_repository::{JobRepository, JobSource, PendingJob};
+use crate::domain::output_format::OutputFormat;
+use crate::domain::preset_catalog;
+use crate::domain::provenance::Provenance;
+use crate::domain::settings_repository::{SettingsRepository, UserSettings};
+use crate::domain::track_edges::{Fades, SilenceTrim};
 use crate::domain::user_repository::UserRepository;
 use crate::infrastructure::ffmpeg_processor::FFmpegProcessor;
+use crate::infrastructure::memory_job_repo::InMemoryJobRepo;
+use crate::infrastructure::sqlite_settings_repo::SqliteSettingsRepo;
 use crate::infrastructure::sqlite_user_repo::SqliteUserRepo;
 use sqlx::sqlite::SqlitePoolOptions;
 use std::sync::Arc;
+use teloxide::net::Download;
 use teloxide::prelude::*;
 use teloxide::types::{
     CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, LabeledPrice, PreCheckoutQuery,
@@ -15,27 +32,53 @@ use tokio::sync::Semaphore;
 use url::Url;
 use urlencoding::encode;
 
-// Клавиатура выбора режима
-fn make_keyboard(url: &str) -> InlineKeyboardMarkup {
-    let buttons = [
-        [InlineKeyboardButton::callback(
-            "🏎 Car Bass",
-            format!("bass|{}", url),
-        )],
-        [InlineKeyboardButton::callback(
-            "🎧 Pure Hi-Fi",
-            format!("hifi|{}", url),
-        )],
-        [InlineKeyboardButton::callback(
-            "🔥 Extreme Low",
-            format!("extreme|{}", url),
-        )],
-        [InlineKeyboardButton::callback(
-            "🌀 8D Surround",
-            format!("8d|{}", url),
-        )],
-    ];
-    InlineKeyboardMarkup::new(buttons)
+// Клавиатура выбора эффектов: кнопки включают/выключают пресеты, внизу — запуск с ценой
+fn make_keyboard(job_id: &str, job: &PendingJob, settings: &UserSettings) -> InlineKeyboardMarkup {
+    let buttons: Vec<InlineKeyboardButton> = preset_catalog::CATALOG
+        .iter()
+        .filter(|entry| (entry.build)(settings).is_some())
+        .map(|entry| {
+            let label = if job.presets.iter().any(|k| k == entry.key) {
+                format!("✅ {}", entry.label)
+            } else {
+                entry.label.to_string()
+            };
+            InlineKeyboardButton::callback(label, format!("t|{}|{}", job_id, entry.key))
+        })
+        .collect();
+
+    let mut rows: Vec<Vec<InlineKeyboardButton>> =
+        buttons.chunks(2).map(|row| row.to_vec()).collect();
+
+    let tags_label = if job.tags.is_empty() {
+        "✏ Теги"
+    } else {
+        "✏ Теги ✓"
+    };
+    let mut settings_row = vec![InlineKeyboardButton::callback(
+        tags_label,
+        format!("e|{}", job_id),
+    )];
+    // В режиме магнитолы формат всегда MP3 — выбирать нечего
+    if settings.head_unit.is_none() {
+        settings_row.push(InlineKeyboardButton::callback(
+            format!("💾 Формат: {}", job.format.label()),
+            format!("f|{}", job_id),
+        ));
+    }
+    rows.push(settings_row);
+
+    let start = if job.presets.is_empty() {
+        "👆 Выбери хотя бы один эффект".to_string()
+    } else {
+        format!("🚀 Погнали — {} кр.", preset_catalog::price(&job.presets))
+    };
+    rows.push(vec![InlineKeyboardButton::callback(
+        start,
+        format!("go|{}", job_id),
+    )]);
+
+    InlineKeyboardMarkup::new(rows)
 }
 
 // Клавиатура оплаты
@@ -62,10 +105,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .execute(&pool)
     .await?;
 
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS user_settings (user_id INTEGER NOT NULL, key TEXT NOT NULL, value TEXT NOT NULL, PRIMARY KEY (user_id, key))",
+    )
+    .execute(&pool)
+    .await?;
+
     // 2. Инициализация сервисов (DI)
     let semaphore = Arc::new(Semaphore::new(3));
-    let audio_service: Arc<dyn AudioService> = Arc::new(FFmpegProcessor);
-    let user_repo: Arc<dyn UserRepository> = Arc::new(SqliteUserRepo::new(pool));
+    let processor = Arc::new(FFmpegProcessor::new());
+    let audio_service: Arc<dyn AudioService> = processor.clone();
+    let calibration_service: Arc<dyn CalibrationService> = processor;
+    let user_repo: Arc<dyn UserRepository> = Arc::new(SqliteUserRepo::new(pool.clone()));
+    let settings_repo: Arc<dyn SettingsRepository> = Arc::new(SqliteSettingsRepo::new(pool));
+    let job_repo: Arc<dyn JobRepository> = Arc::new(InMemoryJobRepo::new());
 
     let bot = Bot::from_env();
 
@@ -77,13 +130,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .endpoint(handle_successful_payment),
         )
         .branch(Update::filter_pre_checkout_query().endpoint(handle_pre_checkout))
+        .branch(
+            Update::filter_message()
+                .filter(|msg: Message| msg.voice().is_some())
+                // Голосовое — запись салона, только если его ждёт /calibrate
+                .filter_async(
+                    |msg: Message, settings_repo: Arc<dyn SettingsRepository>| async move {
+                        settings_repo
+                            .get_settings(msg.chat.id.0)
+                            .await
+                            .awaiting_calibration
+                    },
+                )
+                .endpoint(handle_calibration_recording),
+        )
+        .branch(
+            Update::filter_message()
+                .filter(|msg: Message| msg.photo().is_some())
+                .endpoint(handle_cover_photo),
+        )
+        .branch(
+            Update::filter_message()
+                .filter(|msg: Message| uploaded_audio(&msg).is_some())
+                .endpoint(handle_audio_upload),
+        )
         .branch(Update::filter_message().endpoint(handle_message))
         .branch(Update::filter_callback_query().endpoint(handle_callback));
 
     log::info!("🚀 Бот DeepDrive AI запущен!");
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![audio_service, semaphore, user_repo])
+        .dependencies(dptree::deps![
+            audio_service,
+            calibration_service,
+            semaphore,
+            user_repo,
+            settings_repo,
+            job_repo
+        ])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
@@ -96,6 +180,9 @@ async fn handle_message(
     bot: Bot,
     msg: Message,
     repo: Arc<dyn UserRepository>,
+    settings_repo: Arc<dyn SettingsRepository>,
+    calibration: Arc<dyn CalibrationService>,
+    job_repo: Arc<dyn JobRepository>,
 ) -> ResponseResult<()> {
     let me = bot.get_me().await?;
     let bot_username = me.user.username.expect("Bot must have username");
@@ -173,24 +260,1151 @@ async fn handle_message(
             return Ok(());
         }
 
-        // 3. ОБРАБОТКА ССЫЛОК YOUTUBE
+        // 3. ЛИЧНЫЙ ЭКВАЛАЙЗЕР /EQ
+        if text == "/eq" || text.starts_with("/eq ") {
+            handle_eq_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 4. КАЛИБРОВКА САЛОНА /CALIBRATE
+        if text == "/calibrate" || text == "/calibrate off" {
+            handle_calibrate_command(
+                &bot,
+                &msg,
+                text,
+                settings_repo.as_ref(),
+                calibration.as_ref(),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        // 5. МЕСТО ВОДИТЕЛЯ /SEAT
+        if text == "/seat" || text.starts_with("/seat ") {
+            handle_seat_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 6. РАЗМЕР ШТАТНЫХ ДИНАМИКОВ /SPEAKERS
+        if text == "/speakers" || text.starts_with("/speakers ") {
+            handle_speakers_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 7. РАЗДЕЛЬНЫЕ ВЫХОДЫ САБ/ФРОНТ /CROSSOVER
+        if text == "/crossover" || text.starts_with("/crossover ") {
+            handle_crossover_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 8. АПМИКС В 5.1 /SURROUND
+        if text == "/surround" || text.starts_with("/surround ") {
+            handle_surround_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 9. ФОРМАТ ФАЙЛОВ /FORMAT
+        if text == "/format" || text.starts_with("/format ") {
+            handle_format_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 10. РЕЖИМ ШТАТНОЙ МАГНИТОЛЫ /HEADUNIT
+        if text == "/headunit" || text.starts_with("/headunit ") {
+            handle_head_unit_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 11. ОБРЕЗКА ТИШИНЫ /TRIM И ФЕЙДЫ /FADE
+        if text == "/trim" || text.starts_with("/trim ") {
+            handle_trim_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+        if text == "/fade" || text.starts_with("/fade ") {
+            handle_fade_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 12. ВРАЩЕНИЕ 8D /8D
+        if text == "/8d" || text.starts_with("/8d ") {
+            handle_8d_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 13. ДОЛЯ РЕВЕРБА /REVERB
+        if text == "/reverb" || text.starts_with("/reverb ") {
+            handle_reverb_command(&bot, &msg, text, settings_repo.as_ref()).await?;
+            return Ok(());
+        }
+
+        // 14. ОБРАБОТКА ССЫЛОК YOUTUBE
         if text.contains("youtu") {
             let balance = repo.get_balance(user_id).await;
+            let settings = settings_repo.get_settings(user_id).await;
+            let job = PendingJob::new(
+                user_id,
+                JobSource::Link(text.to_string()),
+                settings.output_format,
+            );
+            job_repo.stop_editing(user_id).await;
+            let job_id = job_repo.create(job.clone()).await;
             bot.send_message(
                 msg.chat.id,
                 format!(
-                    "💳 Твой баланс: <b>{}</b> кредитов.\n\nВыбери режим прокачки:",
+                    "💳 Твой баланс: <b>{}</b> кредитов.\n\nВыбери эффекты (можно несколько) и жми «Погнали»:",
                     balance
                 ),
             )
             .parse_mode(teloxide::types::ParseMode::Html)
-            .reply_markup(make_keyboard(text))
+            .reply_markup(make_keyboard(&job_id, &job, &settings))
             .await?;
         }
+        // Строки с тегами для задания, в котором открыт шаг «✏ Теги»
+        else if let Some((job_id, mut job)) = job_repo.find_editing(user_id).await {
+            match job.tags.apply_text(text) {
+                Ok(()) => {
+                    job_repo.update(&job_id, job.clone()).await;
+                    bot.send_message(
+                        msg.chat.id,
+                        format!(
+                            "✅ Теги обновлены:\n🎵 {}\n👤 {}\n💿 {}\n\nЖми «Погнали» под списком эффектов.",
+                            job.tags.title.as_deref().unwrap_or("как на YouTube"),
+                            job.tags.artist.as_deref().unwrap_or("как на YouTube"),
+                            job.tags.album.as_deref().unwrap_or("—")
+                        ),
+                    )
+                    .await?;
+                }
+                Err(e) => {
+                    bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+                }
+            }
+        }
         // Если просто текст — подсказываем, что делать
         else {
-            bot.send_message(msg.chat.id, "📥 Пришли ссылку на YouTube видео или Shorts!")
+            bot.send_message(
+                msg.chat.id,
+                "📥 Пришли ссылку на YouTube видео или Shorts — или сам аудиофайл!",
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// Сохранить настройки; если база не ответила — сказать пользователю, что ничего не поменялось
+async fn save_settings_or_report(
+    bot: &Bot,
+    chat_id: ChatId,
+    settings_repo: &dyn SettingsRepository,
+    settings: &UserSettings,
+) -> ResponseResult<bool> {
+    if settings_repo
+        .save_settings(chat_id.0, settings)
+        .await
+        .is_ok()
+    {
+        return Ok(true);
+    }
+    bot.send_message(chat_id, "❌ Не удалось сохранить, попробуй позже.")
+        .await?;
+    Ok(false)
+}
+
+// /eq — показать, /eq off — удалить, /eq 60:4:0.7 ... preamp=-3 lufs=-14 — сохранить
+async fn handle_eq_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/eq").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match &settings.custom_eq {
+            Some(eq) => format!("⭐ Твой пресет:\n<code>{}</code>", eq),
+            None => "У тебя пока нет своего пресета.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                🎚 Задай до {} полос в формате <code>частота:усиление:Q</code>, \
+                плюс (по желанию) предусиление и целевую громкость:\n\
+                <code>/eq 60:4:0.7 250:-2 4000:3:1.4 preamp=-3 lufs=-14</code>\n\n\
+                <code>/eq off</code> — удалить пресет",
+                current, MAX_EQ_BANDS
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.custom_eq = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🗑 Личный пресет удалён.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<CustomEq>() {
+        Ok(eq) => {
+            let reply = format!(
+                "✅ Пресет сохранён! Кнопка «⭐ Мой пресет» появится под ссылкой.\n\n<code>{}</code>",
+                eq
+            );
+            settings.custom_eq = Some(eq);
+            if settings_repo
+                .save_settings(user_id, &settings)
+                .await
+                .is_err()
+            {
+                bot.send_message(
+                    msg.chat.id,
+                    "❌ Не удалось сохранить пресет, попробуй позже.",
+                )
                 .await?;
+                return Ok(());
+            }
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /calibrate — прислать тестовый сигнал, /calibrate off — удалить профиль салона
+async fn handle_calibrate_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+    calibration: &dyn CalibrationService,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+
+    if text == "/calibrate off" {
+        let mut settings = settings_repo.get_settings(user_id).await;
+        settings.cabin_profile = None;
+        settings.awaiting_calibration = false;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🗑 Профиль салона удалён.")
+            .await?;
+        return Ok(());
+    }
+
+    match calibration.make_test_signal().await {
+        Ok(path) => {
+            // Сначала флаг: без него голосовое с записью не узнаем
+            let mut settings = settings_repo.get_settings(user_id).await;
+            settings.awaiting_calibration = true;
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                let _ = tokio::fs::remove_file(path).await;
+                return Ok(());
+            }
+
+            let file = teloxide::types::InputFile::file(&path).file_name("calibration.mp3");
+            bot.send_audio(msg.chat.id, file)
+                .caption(
+                    "🎛 <b>Калибровка салона</b>\n\n\
+                    1. Включи этот файл в машине на обычной громкости, эквалайзер магнитолы — в ноль.\n\
+                    2. Сядь на водительское место и запиши <b>голосовое сообщение</b> на всё время шума, \
+                    держа телефон на уровне головы.\n\
+                    3. Отправь голосовое сюда — я построю коррекцию и буду накладывать её на любой пресет.",
+                )
+                .parse_mode(teloxide::types::ParseMode::Html)
+                .await?;
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Ошибка: {}", e))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+// /seat — показать, /seat off — выключить, /seat 70 130 [120%] — сохранить
+async fn handle_seat_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/seat").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match &settings.driver_seat {
+            Some(seat) => format!(
+                "💺 Сейчас: до левого {} см, до правого {} см, ширина {}%",
+                seat.left_cm, seat.right_cm, seat.width_percent
+            ),
+            None => "💺 Режим «место водителя» выключен.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Измерь рулеткой расстояние от головы до левого и правого передних динамиков \
+                и пришли их в сантиметрах. Третьим числом можно задать ширину стерео (100% — как есть):\n\
+                <code>/seat 70 130</code> или <code>/seat 70 130 120%</code>\n\n\
+                <code>/seat off</code> — выключить",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.driver_seat = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "💺 Режим «место водителя» выключен.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<DriverSeat>() {
+        Ok(seat) => {
+            let (left_delay, right_delay) = seat.delays_ms();
+            let reply = format!(
+                "✅ Сохранил! Задержка: левый {:.2} мс, правый {:.2} мс. \
+                Применяется поверх любого пресета.",
+                left_delay, right_delay
+            );
+            settings.driver_seat = Some(seat);
+            if settings_repo
+                .save_settings(user_id, &settings)
+                .await
+                .is_err()
+            {
+                bot.send_message(msg.chat.id, "❌ Не удалось сохранить, попробуй позже.")
+                    .await?;
+                return Ok(());
+            }
+            bot.send_message(msg.chat.id, reply).await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /speakers 13 — диаметр штатных динамиков в см, от него зависит срез для «Штатки»
+async fn handle_speakers_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/speakers").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let size = settings.speaker_size_cm.unwrap_or(16.5);
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "📻 Динамики: <b>{} см</b>, бас ниже {} Гц заменяется гармониками.\n\n\
+                Пришли диаметр своих динамиков в сантиметрах: <code>/speakers 13</code>",
+                size,
+                VirtualBassParams::for_speaker_size(size).cutoff_hz
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    match args.trim_end_matches("см").trim().parse::<f32>() {
+        Ok(size) if (8.0..=30.0).contains(&size) => {
+            settings.speaker_size_cm = Some(size);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Запомнил: {} см. В «📻 Штатке» бас ниже {} Гц будет виртуальным.",
+                    size,
+                    VirtualBassParams::for_speaker_size(size).cutoff_hz
+                ),
+            )
+            .await?;
+        }
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "⚠️ Нужен диаметр от 8 до 30 см, например /speakers 16.5",
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// /8d 0.2 30 40% — скорость вращения, качание по высоте и комната для «8D», /8d off — как было
+async fn handle_8d_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/8d").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let params = settings.surround_8d.unwrap_or_default();
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "🌀 8D сейчас: {} об/с, качание по высоте ±{}°, комната {:.0}%.\n\n\
+                Задай скорость вращения (0.02–1 об/с), высоту (0–60°) и долю комнаты (0–100%):\n\
+                <code>/8d 0.2 30 40%</code> — или только скорость: <code>/8d 0.05</code>\n\n\
+                <code>/8d off</code> — вернуть как было",
+                params.rotation_hz,
+                params.elevation_deg,
+                params.reverb * 100.0
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.surround_8d = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🌀 Готово, 8D снова по умолчанию.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<SurroundParams>() {
+        Ok(params) => {
+            settings.surround_8d = Some(params);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Запомнил: «🌀 8D» будет вращаться со скоростью {} об/с, ±{}° по высоте, комната {:.0}%.",
+                    params.rotation_hz,
+                    params.elevation_deg,
+                    params.reverb * 100.0
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /reverb 40% — доля «мокрого» сигнала в пресетах комнат, /reverb off — своя у каждой комнаты
+async fn handle_reverb_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/reverb").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.reverb_mix {
+            Some(mix) => format!("🏛 Сейчас в комнатах {:.0}% «мокрого» звука.", mix * 100.0),
+            None => "🏛 Сейчас у каждой комнаты своя доля реверба.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Задай, сколько комнаты подмешивать в «Reverb», «Зал», «Клуб», «Собор» и «Комнату» (0–100%):\n\
+                <code>/reverb 40%</code>\n\n\
+                <code>/reverb off</code> — вернуть как было",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.reverb_mix = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🏛 Готово, у каждой комнаты снова своя доля.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.trim_end_matches('%').trim().parse::<f32>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => {
+            settings.reverb_mix = Some(percent / 100.0);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Запомнил: в комнатах будет {}% «мокрого» звука.",
+                    percent
+                ),
+            )
+            .await?;
+        }
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "⚠️ Нужна доля от 0 до 100%, например /reverb 40%",
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// /surround flac 0 -3 — апмикс в 5.1 (формат, центр и LFE в дБ), /surround off — стерео
+async fn handle_surround_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/surround").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.output_mode {
+            OutputMode::Surround(params) => format!(
+                "🔊 Сейчас: 5.1 в {}, центр {:+} дБ, LFE {:+} дБ.",
+                params.codec.extension().to_uppercase(),
+                params.center_db,
+                params.lfe_db
+            ),
+            _ => "🔊 Сейчас присылаю стерео.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Если магнитола играет многоканальные файлы с флешки, \
+                могу разложить трек на 5.1. Формат — flac или ac3, \
+                дальше уровни центра и саба (от -12 до +6 дБ):\n\
+                <code>/surround flac 0 -3</code>\n\n\
+                <code>/surround off</code> — снова стерео",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.output_mode = OutputMode::Stereo;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🔊 Готово, снова присылаю стерео.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<UpmixParams>() {
+        Ok(params) => {
+            settings.output_mode = OutputMode::Surround(params);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Теперь присылаю 5.1 в {}. Раздельные саб/фронт (/crossover) при этом выключены.",
+                    params.codec.extension().to_uppercase()
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /format flac — формат файлов по умолчанию (в задании его можно сменить кнопкой)
+async fn handle_format_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/format").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let formats = OutputFormat::ALL
+            .iter()
+            .map(|f| format!("<code>{}</code> — {}", f.key(), f.label()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "💾 Сейчас присылаю: <b>{}</b>\n\n\
+                Выбери формат по умолчанию, например <code>/format flac</code>:\n{}\n\n\
+                Для отдельного трека формат можно сменить кнопкой под эффектами.",
+                settings.output_format.label(),
+                formats
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    match args.parse::<OutputFormat>() {
+        Ok(format) => {
+            settings.output_format = format;
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!("✅ Теперь присылаю треки в {}.", format.label()),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /headunit on — файлы для штатной магнитолы (/headunit 240 — размер обложки), /headunit off
+async fn handle_head_unit_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/headunit").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.head_unit {
+            Some(mode) => format!(
+                "📟 Режим магнитолы включён, обложка до {} px.",
+                mode.cover_px
+            ),
+            None => "📟 Режим магнитолы выключен.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Если штатная магнитола не видит названия, показывает «кракозябры» \
+                или спотыкается на обложках — включи режим совместимости: \
+                MP3 44.1 кГц, теги ID3v2.3 + ID3v1, маленькая обложка и латиница в имени файла.\n\
+                <code>/headunit on</code> — обложка до 300 px\n\
+                <code>/headunit 200</code> — свой размер (100–1000 px)\n\n\
+                <code>/headunit off</code> — выключить",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.head_unit = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "📟 Готово, режим магнитолы выключен.")
+            .await?;
+        return Ok(());
+    }
+
+    let args = if args == "on" { "" } else { args };
+    match args.parse::<HeadUnitMode>() {
+        Ok(mode) => {
+            settings.head_unit = Some(mode);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Режим магнитолы включён: MP3 44.1 кГц, ID3v2.3 + ID3v1, обложка до {} px.",
+                    mode.cover_px
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /trim -50 — срезать тишину по краям тише порога, /trim off — не трогать
+async fn handle_trim_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/trim").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.silence_trim {
+            Some(trim) => format!(
+                "✂️ Сейчас срезаю тишину по краям (всё тише {} дБ).",
+                trim.threshold_db
+            ),
+            None => "✂️ Сейчас края трека не трогаю.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Чтобы убрать болтовню и тишину в начале и в конце ролика:\n\
+                <code>/trim on</code> — порог -50 дБ\n\
+                <code>/trim -40</code> — свой порог (от -80 до -20, выше — режет смелее)\n\n\
+                <code>/trim off</code> — выключить",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.silence_trim = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "✂️ Готово, края больше не обрезаю.")
+            .await?;
+        return Ok(());
+    }
+
+    let args = if args == "on" { "" } else { args };
+    match args.parse::<SilenceTrim>() {
+        Ok(trim) => {
+            settings.silence_trim = Some(trim);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Буду срезать тишину тише {} дБ в начале и в конце трека.",
+                    trim.threshold_db
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /fade 2 4 — появление за 2 с и затухание за 4 с, /fade off — без фейдов
+async fn handle_fade_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/fade").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.fades {
+            Some(fades) => format!(
+                "🌅 Сейчас: появление {} с, затухание {} с.",
+                fades.fade_in_s, fades.fade_out_s
+            ),
+            None => "🌅 Сейчас фейды выключены.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Задай длину плавного появления и затухания в секундах (0–15):\n\
+                <code>/fade 2 4</code> — или одно число для обоих: <code>/fade 3</code>\n\n\
+                <code>/fade off</code> — выключить",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.fades = None;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🌅 Готово, фейды выключены.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<Fades>() {
+        Ok(fades) => {
+            settings.fades = Some(fades);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Трек будет плавно появляться за {} с и затухать за {} с.",
+                    fades.fade_in_s, fades.fade_out_s
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// /crossover 80 24 — присылать саб и фронт отдельными файлами, /crossover off — один файл
+async fn handle_crossover_command(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    settings_repo: &dyn SettingsRepository,
+) -> ResponseResult<()> {
+    let user_id = msg.chat.id.0;
+    let args = text.trim_start_matches("/crossover").trim();
+    let mut settings = settings_repo.get_settings(user_id).await;
+
+    if args.is_empty() {
+        let current = match settings.output_mode {
+            OutputMode::Crossover(params) => format!(
+                "🔌 Сейчас: раздел на {} Гц, {} дБ/окт — присылаю саб и фронт отдельно.",
+                params.freq_hz, params.slope_db
+            ),
+            OutputMode::Surround(_) => "🔌 Сейчас присылаю 5.1 (/surround).".to_string(),
+            OutputMode::Stereo => "🔌 Сейчас присылаю один стерео-файл.".to_string(),
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "{}\n\n\
+                Для отдельного усилителя саба задай частоту раздела (40–250 Гц) \
+                и крутизну (12, 24 или 48 дБ/окт):\n\
+                <code>/crossover 80 24</code>\n\n\
+                <code>/crossover off</code> — снова один файл",
+                current
+            ),
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    if args == "off" {
+        settings.output_mode = OutputMode::Stereo;
+        if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+            return Ok(());
+        }
+        bot.send_message(msg.chat.id, "🔌 Готово, снова присылаю один файл.")
+            .await?;
+        return Ok(());
+    }
+
+    match args.parse::<CrossoverParams>() {
+        Ok(params) => {
+            settings.output_mode = OutputMode::Crossover(params);
+            if !save_settings_or_report(bot, msg.chat.id, settings_repo, &settings).await? {
+                return Ok(());
+            }
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Теперь каждый трек приходит двумя файлами: моно-саб ниже {} Гц и стерео-фронт выше.",
+                    params.freq_hz
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
+
+// Фото — обложка для задания, в котором открыт шаг «✏ Теги»
+async fn handle_cover_photo(
+    bot: Bot,
+    msg: Message,
+    job_repo: Arc<dyn JobRepository>,
+) -> ResponseResult<()> {
+    let Some(photo) = msg
+        .photo()
+        .and_then(|sizes| sizes.iter().max_by_key(|p| p.width))
+    else {
+        return Ok(());
+    };
+    let Some((job_id, mut job)) = job_repo.find_editing(msg.chat.id.0).await else {
+        bot.send_message(
+            msg.chat.id,
+            "🖼 Чтобы поставить своё фото на обложку, сначала пришли ссылку и нажми «✏ Теги».",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    job.tags.cover_file_id = Some(photo.file.id.to_string());
+    job_repo.update(&job_id, job).await;
+    bot.send_message(
+        msg.chat.id,
+        "✅ Обложка сохранена — обрежу её до квадрата. Жми «Погнали» под списком эффектов.",
+    )
+    .await?;
+    Ok(())
+}
+
+// Аудио из чата: обычный трек или файл, отправленный документом
+fn uploaded_audio(msg: &Message) -> Option<(&teloxide::types::FileMeta, Option<String>)> {
+    if let Some(audio) = msg.audio() {
+        return Some((&audio.file, audio.file_name.clone()));
+    }
+    msg.document()
+        .filter(|doc| {
+            doc.mime_type
+                .as_ref()
+                .is_some_and(|mime| mime.type_().as_str() == "audio")
+        })
+        .map(|doc| (&doc.file, doc.file_name.clone()))
+}
+
+// Присланный аудиофайл — такое же задание, как ссылка. Если трек уже прошёл
+// через бота (теги CARBOT_*), предупреждаем: повторный буст даст перегруз
+async fn handle_audio_upload(
+    bot: Bot,
+    msg: Message,
+    service: Arc<dyn AudioService>,
+    repo: Arc<dyn UserRepository>,
+    settings_repo: Arc<dyn SettingsRepository>,
+    job_repo: Arc<dyn JobRepository>,
+) -> ResponseResult<()> {
+    let Some((file, file_name)) = uploaded_audio(&msg) else {
+        return Ok(());
+    };
+    let user_id = msg.chat.id.0;
+
+    if file.size > MAX_UPLOAD_BYTES {
+        bot.send_message(
+            msg.chat.id,
+            "⚠️ Файл больше 20 МБ — Telegram не даёт ботам скачивать такие. Пришли ссылку на YouTube.",
+        )
+        .await?;
+        return Ok(());
+    }
+    let file_name = file_name.unwrap_or_else(|| "track.mp3".to_string());
+
+    // Теги читаем сразу, а сам файл скачаем ещё раз при запуске — чтобы не держать его на диске
+    let suffix = format!("upload.{}", upload_extension(&file_name));
+    let previous = match download_telegram_file(&bot, &file.id.to_string(), &suffix).await {
+        Some(path) => {
+            let provenance = service.read_provenance(&path).await;
+            let _ = tokio::fs::remove_file(&path).await;
+            provenance
+        }
+        None => None,
+    };
+
+    let balance = repo.get_balance(user_id).await;
+    let settings = settings_repo.get_settings(user_id).await;
+    let job = PendingJob {
+        previous: previous.clone(),
+        ..PendingJob::new(
+            user_id,
+            JobSource::Upload {
+                file_id: file.id.to_string(),
+                file_name,
+            },
+            settings.output_format,
+        )
+    };
+    job_repo.stop_editing(user_id).await;
+    let job_id = job_repo.create(job.clone()).await;
+
+    let mut text = String::new();
+    if let Some(previous) = &previous {
+        let labels = previous.labels();
+        text.push_str(&format!(
+            "♻️ <b>Этот трек уже прошёл через бота</b>\n\
+            Эффекты: {}\n\
+            Громкость: {} LUFS (версия бота {})\n\
+            Повторный буст баса или «Трассы» не дам — будет перегруз.\n\n",
+            if labels.is_empty() {
+                "—".to_string()
+            } else {
+                labels.join(", ")
+            },
+            previous.loudness.lufs,
+            previous.bot_version
+        ));
+    }
+    text.push_str(&format!(
+        "💳 Твой баланс: <b>{}</b> кредитов.\n\nВыбери эффекты (можно несколько) и жми «Погнали»:",
+        balance
+    ));
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .reply_markup(make_keyboard(&job_id, &job, &settings))
+        .await?;
+    Ok(())
+}
+
+// Скачивает файл из Telegram во временный файл рядом с ботом
+async fn download_telegram_file(
+    bot: &Bot,
+    file_id: &str,
+    suffix: &str,
+) -> Option<std::path::PathBuf> {
+    let file = bot
+        .get_file(teloxide::types::FileId(file_id.to_string()))
+        .await
+        .ok()?;
+    let path = std::path::PathBuf::from(format!("{}_{}", uuid::Uuid::new_v4(), suffix));
+    let mut dst = tokio::fs::File::create(&path).await.ok()?;
+    if bot.download_file(&file.path, &mut dst).await.is_err() {
+        let _ = tokio::fs::remove_file(&path).await;
+        return None;
+    }
+    Some(path)
+}
+
+// Голосовое сообщение — запись тестового сигнала в салоне
+async fn handle_calibration_recording(
+    bot: Bot,
+    msg: Message,
+    settings_repo: Arc<dyn SettingsRepository>,
+    calibration: Arc<dyn CalibrationService>,
+) -> ResponseResult<()> {
+    let Some(voice) = msg.voice() else {
+        return Ok(());
+    };
+    let user_id = msg.chat.id.0;
+
+    bot.send_message(msg.chat.id, "🎧 Слушаю твой салон...")
+        .await?;
+
+    let file = bot.get_file(voice.file.id.clone()).await?;
+    let path = std::path::PathBuf::from(format!("{}_cabin.ogg", uuid::Uuid::new_v4()));
+    let downloaded = match tokio::fs::File::create(&path).await {
+        Ok(mut dst) => bot.download_file(&file.path, &mut dst).await.is_ok(),
+        Err(_) => false,
+    };
+
+    let result = if downloaded {
+        calibration.analyse_recording(&path).await
+    } else {
+        Err(AudioError::DownloadError(
+            "Не удалось получить запись".into(),
+        ))
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+
+    match result {
+        Ok(profile) => {
+            let summary = if profile.bands.is_empty() {
+                "Салон звучит ровно — коррекция не нужна 👌".to_string()
+            } else {
+                profile
+                    .bands
+                    .iter()
+                    .map(|b| format!("{} Гц: {:+.1} дБ", b.freq, b.gain))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            let mut settings = settings_repo.get_settings(user_id).await;
+            settings.cabin_profile = Some(profile);
+            settings.awaiting_calibration = false;
+            if !save_settings_or_report(&bot, msg.chat.id, settings_repo.as_ref(), &settings)
+                .await?
+            {
+                return Ok(());
+            }
+
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ <b>Профиль салона сохранён</b> и будет применяться ко всем трекам.\n\n\
+                    <code>{}</code>\n\n\
+                    <code>/calibrate off</code> — отключить",
+                    summary
+                ),
+            )
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
+        }
+        Err(e) => {
+            // Флаг не снимаем: можно сразу записать ещё раз
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "❌ Ошибка: {}\n\nЗапиши голосовое ещё раз или отмени: /calibrate off",
+                    e
+                ),
+            )
+            .await?;
         }
     }
     Ok(())
@@ -201,6 +1415,8 @@ async fn handle_callback(
     q: CallbackQuery,
     service: Arc<dyn AudioService>,
     repo: Arc<dyn UserRepository>,
+    settings_repo: Arc<dyn SettingsRepository>,
+    job_repo: Arc<dyn JobRepository>,
     semaphore: Arc<Semaphore>,
 ) -> ResponseResult<()> {
     let user_id = q.from.id.0 as i64;
@@ -218,29 +1434,140 @@ async fn handle_callback(
             return Ok(());
         }
 
-        // ОБРАБОТКА ПРЕСЕТОВ
+        // ОБРАБОТКА ПРЕСЕТОВ: t|job|preset — переключить, f|job — сменить формат,
+        // go|job — запустить
         let parts: Vec<&str> = data.split('|').collect();
-        if parts.len() < 2 {
+        let job_id = match parts.as_slice() {
+            ["t", job_id, _] | ["f", job_id] | ["e", job_id] | ["go", job_id] => *job_id,
+            _ => return Ok(()),
+        };
+
+        // Запуск сразу забирает задание: второе нажатие «Погнали» его уже не найдёт
+        // и не спишет кредиты повторно. Если запуск не состоялся — кладём обратно
+        let starting = matches!(parts.as_slice(), ["go", _]);
+        // Без сообщения некуда показать ход обработки — задание не трогаем и не списываем
+        if starting && q.message.is_none() {
+            bot.answer_callback_query(q.id)
+                .text("Сообщение устарело — пришли ссылку ещё раз")
+                .await?;
             return Ok(());
         }
+        let found = if starting {
+            job_repo.remove(job_id).await
+        } else {
+            job_repo.get(job_id).await
+        };
+        let mut job = match found {
+            Some(job) if job.user_id == user_id => job,
+            other => {
+                if let (true, Some(job)) = (starting, other) {
+                    job_repo.restore(job_id, job).await;
+                }
+                bot.answer_callback_query(q.id)
+                    .text("Задание устарело — пришли ссылку ещё раз")
+                    .await?;
+                return Ok(());
+            }
+        };
+        let settings = settings_repo.get_settings(user_id).await;
 
-        let preset_raw = parts[0];
-        let url = parts[1];
+        if let ["e", _] = parts.as_slice() {
+            job_repo.stop_editing(user_id).await;
+            job.editing_tags = true;
+            job_repo.update(job_id, job.clone()).await;
+            bot.answer_callback_query(q.id).await?;
+            bot.send_message(
+                chat_id,
+                "✏ <b>Теги и обложка</b>\n\n\
+                Пришли фото — оно станет обложкой.\n\
+                Теги — текстом, по строке на поле:\n\
+                <code>Название: Кукла колдуна</code>\n\
+                <code>Исполнитель: Король и Шут</code>\n\
+                <code>Альбом: Акустический альбом</code>\n\n\
+                Пустое значение (<code>Альбом:</code>) возвращает как было. \
+                Когда закончишь — жми «Погнали» под списком эффектов.",
+            )
+            .parse_mode(teloxide::types::ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
 
-        let preset = match preset_raw {
-            "bass" => AudioPreset::CarBass,
-            "hifi" => AudioPreset::PureHiFi,
-            "extreme" => AudioPreset::ExtremeLow,
-            "8d" => AudioPreset::Surround8D,
-            _ => return Ok(()),
-        };
+        if let ["f", _] = parts.as_slice() {
+            job.format = job.format.next();
+            job_repo.update(job_id, job.clone()).await;
+            bot.answer_callback_query(q.id).await?;
+            if let Some(msg) = q.message {
+                bot.edit_message_reply_markup(chat_id, msg.id())
+                    .reply_markup(make_keyboard(job_id, &job, &settings))
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        if let ["t", _, key] = parts.as_slice() {
+            let Some(entry) = preset_catalog::find(key) else {
+                return Ok(());
+            };
+            if (entry.build)(&settings).is_none() {
+                bot.answer_callback_query(q.id)
+                    .text("Сначала настрой свой пресет командой /eq")
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
+
+            preset_catalog::toggle(&mut job.presets, key);
+            job_repo.update(job_id, job.clone()).await;
+            bot.answer_callback_query(q.id).await?;
+            if let Some(msg) = q.message {
+                bot.edit_message_reply_markup(chat_id, msg.id())
+                    .reply_markup(make_keyboard(job_id, &job, &settings))
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        let presets = preset_catalog::resolve(&job.presets, &settings);
+        if presets.is_empty() {
+            job_repo.restore(job_id, job).await;
+            bot.answer_callback_query(q.id)
+                .text("Выбери хотя бы один эффект 👆")
+                .await?;
+            return Ok(());
+        }
+
+        // Второй буст поверх первого — перегруз; блокируем до списания кредитов
+        if let Some(previous) = &job.previous {
+            let repeated: Vec<&str> = previous
+                .repeated_boosts(&job.presets)
+                .iter()
+                .map(|entry| entry.label)
+                .collect();
+            if !repeated.is_empty() {
+                let message = format!(
+                    "🛑 Этот трек уже прокачан ботом — {} поверх даст перегруз. Сними этот эффект.",
+                    repeated.join(", ")
+                );
+                job_repo.restore(job_id, job).await;
+                bot.answer_callback_query(q.id)
+                    .text(message)
+                    .show_alert(true)
+                    .await?;
+                return Ok(());
+            }
+        }
 
         // Проверка баланса ПЕРЕД запуском скачивания
-        if !repo.use_credit(user_id).await {
+        let price = preset_catalog::price(&job.presets);
+        if !repo.use_credits(user_id, price).await {
+            job_repo.restore(job_id, job).await;
             bot.answer_callback_query(q.id).await?;
             bot.send_message(
                 chat_id,
-                "⚠️ У тебя 0 кредитов. Пополни баланс для продолжения! ⭐️",
+                format!(
+                    "⚠️ Не хватает кредитов: эта комбинация стоит {}. Пополни баланс для продолжения! ⭐️",
+                    price
+                ),
             )
             .reply_markup(make_payment_keyboard())
             .await?;
@@ -251,31 +1578,167 @@ async fn handle_callback(
             let _permit = semaphore.acquire().await.unwrap();
             let _ = bot.answer_callback_query(q.id).await;
 
-            bot.edit_message_text(chat_id, msg.id(), "🏎 Запускаю двигатели... Процесс пошел!")
-                .await?;
+            // Кнопка моно-версии висит под готовым треком — у него подпись, а не текст
+            let started = "🏎 Запускаю двигатели... Процесс пошел!";
+            if bot
+                .edit_message_text(chat_id, msg.id(), started)
+                .await
+                .is_err()
+            {
+                bot.send_message(chat_id, started).await?;
+            }
+
+            // Обложку пользователя скачиваем только сейчас: до запуска он мог прислать другую
+            let custom_cover = match &job.tags.cover_file_id {
+                Some(file_id) => download_telegram_file(&bot, file_id, "cover.jpg").await,
+                None => None,
+            };
+
+            let provenance = Provenance::new(job_id, &job.presets, &presets);
+            let options = ProcessingOptions {
+                presets,
+                cabin: settings.cabin_profile,
+                seat: settings.driver_seat,
+                output: settings.output_mode,
+                trim: settings.silence_trim,
+                fades: settings.fades,
+                format: job.format,
+                head_unit: settings.head_unit,
+                tags: job.tags.clone(),
+                custom_cover,
+                mono_safe: job.mono_safe,
+                provenance,
+            };
+            // Моно-безопасный вариант помогает, только если фазу "развалил" наш 8D
+            let has_mono_safe = !job.mono_safe && service.has_mono_safe_variant(&options.presets);
+
+            let result = match &job.source {
+                JobSource::Link(url) => service.process_track(url, &options).await,
+                JobSource::Upload { file_id, file_name } => {
+                    let suffix = format!("upload.{}", upload_extension(file_name));
+                    match download_telegram_file(&bot, file_id, &suffix).await {
+                        Some(path) => {
+                            let result = service.process_upload(&path, file_name, &options).await;
+                            let _ = tokio::fs::remove_file(&path).await;
+                            result
+                        }
+                        None => Err(AudioError::DownloadError(
+                            "Не удалось скачать файл из Telegram".into(),
+                        )),
+                    }
+                }
+            };
 
-            match service.process_track(url, preset).await {
-                Ok((path, meta)) => {
+            match result {
+                Ok((files, meta)) => {
                     let mins = meta.duration / 60;
                     let secs = meta.duration % 60;
                     let duration_str = format!("{:02}:{:02}", mins, secs);
 
-                    let file = teloxide::types::InputFile::file(&path)
-                        .file_name(format!("{}.mp3", meta.title));
+                    let loses_in_mono = files
+                        .iter()
+                        .any(|f| f.mono.is_some_and(|m| m.loses_content()));
+                    let mono_retry = if loses_in_mono && has_mono_safe {
+                        let retry = PendingJob {
+                            mono_safe: true,
+                            editing_tags: false,
+                            ..job.clone()
+                        };
+                        let price = preset_catalog::price(&retry.presets);
+                        let retry_id = job_repo.create(retry).await;
+                        Some(InlineKeyboardMarkup::new([[
+                            InlineKeyboardButton::callback(
+                                format!("📻 Моно-безопасная версия — {} кр.", price),
+                                format!("go|{}", retry_id),
+                            ),
+                        ]]))
+                    } else {
+                        None
+                    };
 
-                    let _ = bot.send_audio(chat_id, file)
-                        .caption(format!(
-                            "✅ <b>Готово для авто!</b>\n\n🎵 {}\n👤 {}\n⏱ Длительность: <code>{}</code>", 
-                            meta.title, meta.artist, duration_str
-                        ))
-                        .parse_mode(teloxide::types::ParseMode::Html)
-                        .await;
-                    let _ = tokio::fs::remove_file(path).await;
+                    for output in files {
+                        let (stem, part) = match &output.suffix {
+                            Some(suffix) => (
+                                format!("{} ({})", meta.title, suffix),
+                                format!("\n🔌 Канал: <b>{}</b>", suffix),
+                            ),
+                            None => (meta.title.clone(), String::new()),
+                        };
+                        let file_name = if options.head_unit.is_some() {
+                            safe_file_name(&stem, output.extension)
+                        } else {
+                            format!("{}.{}", stem, output.extension)
+                        };
+                        let file =
+                            teloxide::types::InputFile::file(&output.path).file_name(file_name);
+                        let mut caption = format!(
+                            "✅ <b>Готово для авто!</b>\n\n🎵 {}\n👤 {}\n⏱ Длительность: <code>{}</code>{}",
+                            meta.title, meta.artist, duration_str, part
+                        );
+                        let mut music = Vec::new();
+                        if let Some(bpm) = meta.bpm {
+                            music.push(format!("🥁 <code>{:.0}</code> BPM", bpm));
+                        }
+                        if let Some(key) = meta.key {
+                            music.push(format!("🎼 <code>{}</code> ({})", key, key.camelot()));
+                        }
+                        if !music.is_empty() {
+                            caption.push_str(&format!("\n{}", music.join(" · ")));
+                        }
+                        let mut keyboard = None;
+                        // Предупреждаем, только если есть что предложить взамен
+                        if let Some(check) = output.mono.filter(|m| m.loses_content())
+                            && mono_retry.is_some()
+                        {
+                            caption.push_str(&format!(
+                                "\n\n⚠️ В моно (одна колонка, моно-AUX) трек теряет {:.1} дБ — часть звука пропадёт.",
+                                check.mono_loss_db
+                            ));
+                            keyboard = mono_retry.clone();
+                        }
+
+                        // Аудиоплеер Telegram понимает только MP3/M4A — остальное шлём файлом
+                        let _ = if matches!(output.extension, "mp3" | "m4a") {
+                            let mut request = bot
+                                .send_audio(chat_id, file)
+                                .caption(caption)
+                                .parse_mode(teloxide::types::ParseMode::Html);
+                            if let Some(keyboard) = keyboard {
+                                request = request.reply_markup(keyboard);
+                            }
+                            request.await
+                        } else {
+                            bot.send_document(chat_id, file)
+                                .caption(caption)
+                                .parse_mode(teloxide::types::ParseMode::Html)
+                                .await
+                        };
+                        let _ = tokio::fs::remove_file(output.path).await;
+                    }
                 }
                 Err(e) => {
-                    let _ = bot.send_message(chat_id, format!("❌ Ошибка: {}", e)).await;
+                    // Трек не получился — кредиты возвращаем
+                    let note = match repo.add_balance(user_id, price).await {
+                        Ok(()) => format!("Списанные {} кр. вернули на баланс.", price),
+                        Err(err) => {
+                            log::error!(
+                                "Не вернули {} кр. пользователю {}: {}",
+                                price,
+                                user_id,
+                                err
+                            );
+                            "Кредиты вернуть не удалось — ошибку записали, вернём вручную."
+                                .to_string()
+                        }
+                    };
+                    let _ = bot
+                        .send_message(chat_id, format!("❌ Ошибка: {}\n{}", e, note))
+                        .await;
                 }
             }
+            if let Some(path) = &options.custom_cover {
+                let _ = tokio::fs::remove_file(path).await;
+            }
         }
     }
     Ok(())